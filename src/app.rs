@@ -1,9 +1,22 @@
 use crate::{
-    camera::CameraControl, controller::Team, entity::Entity, input::InputAxis,
-    mouse_display::MouseDisplay, projectile::Projectile, util,
+    camera::CameraControl,
+    computer_controller::Weapon,
+    controller::{EntityController, MotionController, ShootingController},
+    debris::Debris,
+    entity::Entity,
+    faction::{Factions, Relationship, Team},
+    gravity::Gravity,
+    guidance::Guidance,
+    input::InputAxis,
+    mouse_display::MouseDisplay,
+    particle::{Emitter, Particles},
+    pickup::Pickup,
+    projectile::Projectiles,
+    rules::CollisionRules,
+    util,
 };
 use macroquad::prelude::*;
-use nalgebra::{Point2, UnitComplex, vector};
+use nalgebra::{Point2, UnitComplex};
 use std::time::Instant;
 use thunderdome::{Arena, Index};
 
@@ -15,12 +28,29 @@ pub struct App {
     pub camera: Camera2D,
     pub camera_control: CameraControl,
     pub entities: Arena<Entity>,
-    pub projectiles: Arena<Projectile>,
+    pub projectiles: Projectiles,
+    pub debris: Vec<Debris>,
+    pub particles: Particles,
+    pub pickups: Vec<Pickup>,
+    pub collision_rules: CollisionRules,
+    pub factions: Factions,
+    pub gravity: Gravity,
     pub mouse: MouseDisplay,
+    /// Entities destroyed during the entity updates, removed with debris and drops once every
+    /// entity has updated, since the arena can't change while it is being iterated
+    pub deaths: Vec<Index>,
+    /// Entities created during the current timestep, inserted once every entity has updated
+    pub spawns: Vec<Entity>,
+    /// Entities leaving quietly during the current timestep, without debris or drops
+    pub despawns: Vec<Index>,
 }
 
 impl App {
     pub const MAX_UPDATES_PER_FRAME: usize = 5;
+    /// Number of projectiles that can be live at once before their storage has to grow
+    pub const PROJECTILE_CAPACITY: usize = 1 << 16;
+    /// Number of particles that can be live at once, any more are dropped
+    pub const PARTICLE_BUDGET: usize = 4096;
 
     pub fn from_ups(updates_per_second: f32) -> Self {
         use std::f32::consts::TAU;
@@ -41,7 +71,13 @@ impl App {
             normalized: true,
         };
         let entities = Arena::new();
-        let projectiles = Arena::new();
+        let projectiles = Projectiles::with_capacity(Self::PROJECTILE_CAPACITY);
+        let debris = Vec::new();
+        let particles = Particles::with_budget(Self::PARTICLE_BUDGET);
+        let pickups = Vec::new();
+        let collision_rules = CollisionRules::default();
+        let factions = Factions::default();
+        let gravity = Gravity::default();
         let mouse = MouseDisplay::from_speed(-TAU / 6.0, TAU / 12.0);
         Self {
             timestep_length,
@@ -52,7 +88,16 @@ impl App {
             camera_control,
             entities,
             projectiles,
+            debris,
+            particles,
+            pickups,
+            collision_rules,
+            factions,
+            gravity,
             mouse,
+            deaths: Vec::new(),
+            spawns: Vec::new(),
+            despawns: Vec::new(),
         }
     }
 
@@ -60,15 +105,43 @@ impl App {
         clear_background(BLACK);
         update_camera(&mut self.camera);
 
-        for (_, projectile) in &self.projectiles {
-            projectile.draw();
+        self.gravity.draw();
+        for pickup in &self.pickups {
+            pickup.draw();
         }
+        for debris in &self.debris {
+            debris.draw();
+        }
+        self.projectiles.draw();
+        self.particles.draw();
 
         for (_, entity) in &self.entities {
             entity.draw();
+
+            if let Some(ShootingController::Player(controller)) = entity
+                .controller
+                .as_ref()
+                .and_then(|controller| controller.shooting.as_ref())
+            {
+                controller.draw_trajectory(entity, &self.gravity, self.timestep_length);
+            }
         }
 
         self.mouse.draw();
+
+        // Drawn last, since it switches to screen space
+        let boss = self.entities.iter().find(|(_, entity)| {
+            entity.boss.is_some()
+                && entity
+                    .controller
+                    .as_ref()
+                    .is_some_and(|controller| !controller.targets.is_empty())
+        });
+        if let Some((_, entity)) = boss
+            && let Some(boss) = &entity.boss
+        {
+            boss.draw_health_bar(entity, entity.color);
+        }
     }
 
     pub fn update(&mut self) {
@@ -78,6 +151,16 @@ impl App {
 
         self.mouse.update_mouse_position(&self.camera);
 
+        for (_, entity) in &mut self.entities {
+            if let Some(MotionController::Player(controller)) = entity
+                .controller
+                .as_mut()
+                .and_then(|controller| controller.motion.as_mut())
+            {
+                controller.latch_presses();
+            }
+        }
+
         let updates = (self.update_time / self.timestep_length) as usize;
         for _ in 0..updates.min(Self::MAX_UPDATES_PER_FRAME) {
             self.run_timestep();
@@ -89,48 +172,162 @@ impl App {
     pub fn run_timestep(&mut self) {
         let app = unsafe { &mut *(self as *mut App) }; // Nececary due to the borrow checker, causes UB if safety rules are broken
 
-        let mut to_remove = Vec::new();
-        for (index, projectile) in &mut self.projectiles {
-            if projectile.update(self.timestep_length, app).is_none() {
-                to_remove.push(index);
+        let existing_debris = self.debris.len();
+
+        self.gravity.update_sources(&self.entities);
+        self.projectiles.update(self.timestep_length, app);
+
+        for (index, entity) in &mut self.entities {
+            if entity.update(index, self.timestep_length, app).is_none() {
+                self.deaths.push(index);
             }
         }
+        for index in std::mem::take(&mut self.deaths) {
+            self.remove_entity(index);
+        }
+        for index in self.despawns.drain(..) {
+            self.entities.remove(index);
+        }
+        for entity in self.spawns.drain(..) {
+            self.entities.insert(entity);
+        }
 
-        for index in to_remove {
-            self.projectiles.remove(index);
+        for debris in &mut self.debris {
+            let gravity = self.gravity.acceleration_at(debris.position, None);
+            debris.update(gravity, self.timestep_length);
         }
 
-        for (index, entity) in &mut self.entities {
-            entity.update(index, self.timestep_length, app);
+        // Every piece that broke off this timestep, whether it was shot off or shed on death
+        for debris in &self.debris[existing_debris..] {
+            Emitter::ARMOR.emit(
+                debris.position,
+                UnitComplex::new(debris.angle),
+                debris.velocity,
+                debris.color,
+                &mut self.particles,
+            );
         }
+        self.debris.retain(|debris| !debris.is_expired());
+        self.particles.update(self.timestep_length);
+
+        self.update_pickups();
 
         self.camera_control
             .update_camera(&mut self.camera, self.timestep_length);
         self.mouse.update_mouse_position(&self.camera);
     }
 
+    /// Drifts every pickup and hands it to the first player entity touching it.
+    pub fn update_pickups(&mut self) {
+        for pickup in &mut self.pickups {
+            let gravity = self.gravity.acceleration_at(pickup.position, None);
+            pickup.update(gravity, self.timestep_length);
+
+            let collector = self.entities.iter_mut().find(|(_, entity)| {
+                entity
+                    .controller
+                    .as_ref()
+                    .is_some_and(EntityController::is_player)
+                    && pickup.is_touching(entity)
+            });
+
+            if let Some((_, entity)) = collector {
+                pickup.apply(entity);
+                pickup.age = pickup.lifetime;
+            }
+        }
+        self.pickups.retain(|pickup| !pickup.is_expired());
+    }
+
+    /// Removes a dead entity, leaving its remaining armor behind as debris and bursting apart.
+    pub fn remove_entity(&mut self, index: Index) {
+        if let Some(entity) = self.entities.remove(index) {
+            entity.shed_debris(&mut self.debris);
+            entity
+                .drops
+                .roll(entity.position, entity.velocity, &mut self.pickups);
+            Emitter::DEATH.emit(
+                entity.position,
+                UnitComplex::identity(),
+                entity.velocity,
+                entity.color,
+                &mut self.particles,
+            );
+        }
+    }
+
+    /// Detaches every satellite orbiting `parent`.
+    pub fn release_satellites(&mut self, parent: Index) {
+        for (_, entity) in &mut self.entities {
+            if entity
+                .satellite
+                .is_some_and(|satellite| satellite.parent == parent)
+            {
+                entity.detach();
+            }
+        }
+    }
+
+    /// Alerts the entity at `index` to `sender`, along with its nearby allies.
+    pub fn alert(&mut self, index: Index, sender: Index) {
+        let Some(entity) = self.entities.get(index) else {
+            return;
+        };
+        let (position, team) = (entity.position, entity.team);
+
+        for (other_index, other) in &mut self.entities {
+            if other_index == sender
+                || (other_index != index
+                    && (self.factions.get(team, other.team) != Relationship::Allied
+                        || util::length_squared(other.position - position)
+                            > EntityController::ALERT_DISTANCE.powi(2)))
+            {
+                continue;
+            }
+
+            if let Some(controller) = &mut other.controller {
+                controller.alert(sender);
+            }
+        }
+    }
+
+    /// Pushes every entity within `radius` away from `position`, weaker towards the edge.
+    pub fn explode(&mut self, position: Point2<f32>, radius: f32, impulse: f32) {
+        for (_, entity) in &mut self.entities {
+            let displacement = entity.position - position;
+            let distance = util::length(displacement);
+            let reach = radius + entity.radius;
+
+            if distance >= reach || distance == 0.0 {
+                continue;
+            }
+
+            let falloff = 1.0 - distance / reach;
+            entity.apply_impulse(displacement / distance * impulse * falloff);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn insert_projectile(
         &mut self,
-        initial_speed: f32,
-        speed_exponent: f32,
+        weapon: &Weapon,
         aim: UnitComplex<f32>,
         position: Point2<f32>,
         offset_radius: f32,
         color: Color,
         team: Team,
         sender: Index,
+        guidance: Option<Guidance>,
     ) {
-        self.projectiles.insert(Projectile::from_speed(
-            initial_speed,
-            speed_exponent,
+        self.projectiles.insert_from_weapon(
+            weapon,
             aim,
             position + util::displacement_from_angle(aim, offset_radius),
-            vector![1.0, 4.0],
-            1.0,
             color,
             sender,
             Some(team),
-        ));
+            guidance,
+        );
     }
 }
 
@@ -0,0 +1,170 @@
+use macroquad::prelude::*;
+use nalgebra::{Point2, UnitComplex};
+use thunderdome::Index;
+
+use crate::{
+    app::App,
+    components::ArmorSlot,
+    damage::{Damage, DamageKind},
+    entity::Entity,
+    util,
+};
+
+/// A continuous weapon that instantly hits the first armor piece along its aim.
+#[derive(Clone, Debug)]
+pub struct Beam {
+    pub damage_per_second: f32,
+    pub kind: DamageKind,
+    pub range: f32,
+    pub width: f32,
+    /// Heat gained per second while firing. The beam shuts off at 1.0 until it has fully cooled.
+    pub heating: f32,
+    /// Heat lost per second while not firing
+    pub cooling: f32,
+    pub heat: f32,
+    pub overheated: bool,
+    /// Damage that has built up without yet adding up to a whole point
+    pub accumulated_damage: f32,
+    /// The start and end of the beam if it fired during the last update
+    pub trace: Option<(Point2<f32>, Point2<f32>)>,
+}
+
+impl Beam {
+    pub fn from_damage(
+        damage_per_second: f32,
+        kind: DamageKind,
+        range: f32,
+        width: f32,
+        heating: f32,
+        cooling: f32,
+    ) -> Self {
+        Self {
+            damage_per_second,
+            kind,
+            range,
+            width,
+            heating,
+            cooling,
+            heat: 0.0,
+            overheated: false,
+            accumulated_damage: 0.0,
+            trace: None,
+        }
+    }
+
+    /// Fires along `aim` if it is `Some` and the beam isn't overheated, otherwise cools down.
+    pub fn update(
+        &mut self,
+        aim: Option<UnitComplex<f32>>,
+        index: Index,
+        entity: &Entity,
+        delta_seconds: f32,
+        app: &mut App,
+    ) {
+        self.trace = None;
+
+        let aim = match aim {
+            Some(aim) if !self.overheated => aim,
+            _ => {
+                self.heat = (self.heat - self.cooling * delta_seconds).max(0.0);
+                if self.heat <= 0.0 {
+                    self.overheated = false;
+                }
+                return;
+            }
+        };
+
+        self.heat += self.heating * delta_seconds;
+        if self.heat >= 1.0 {
+            self.heat = 1.0;
+            self.overheated = true;
+        }
+
+        let start = entity.position + util::displacement_from_angle(aim, entity.radius + 4.0);
+
+        let mut hit = None;
+        let mut length = self.range;
+        for (other_index, other) in &app.entities {
+            if other_index == index {
+                continue;
+            }
+
+            let Some(multiplier) = app
+                .collision_rules
+                .get(Some(entity.team), other.team, &app.factions)
+                .damage_multiplier()
+            else {
+                continue;
+            };
+
+            if let Some(distance) = other.shield_raycast(start, aim, length, self.kind) {
+                length = distance;
+                hit = Some((other_index, BeamHit::Shield, multiplier));
+            } else if let Some((distance, slot)) = other.raycast(start, aim, length) {
+                length = distance;
+                hit = Some((other_index, BeamHit::Armor(slot), multiplier));
+            }
+        }
+
+        self.trace = Some((start, start + util::displacement_from_angle(aim, length)));
+
+        let Some((hit, part, multiplier)) = hit else {
+            return;
+        };
+
+        self.accumulated_damage += self.damage_per_second * multiplier * delta_seconds;
+        let amount = self.accumulated_damage.floor();
+        self.accumulated_damage -= amount;
+
+        let target = &mut app.entities[hit];
+        if amount > 0.0 {
+            let damage = Damage::from_amount(amount as u16, self.kind);
+            match part {
+                BeamHit::Armor(slot) => target.damage(slot, damage, &mut app.debris),
+                BeamHit::Shield => {
+                    if let Some(shield) = &mut target.shield {
+                        shield.absorb(damage);
+                    }
+                }
+            }
+        }
+
+        app.alert(hit, index);
+
+        // Beams fire while entities are updating, so the removal has to wait for the loop to end
+        let target = &mut app.entities[hit];
+        if target.check_deletion().is_none() {
+            app.deaths.push(hit);
+        }
+    }
+
+    pub fn draw(&self, color: Color) {
+        let Some((start, end)) = self.trace else {
+            return;
+        };
+
+        draw_line(
+            start.x,
+            start.y,
+            end.x,
+            end.y,
+            self.width,
+            Color { a: 0.5, ..color },
+        );
+        draw_line(
+            start.x,
+            start.y,
+            end.x,
+            end.y,
+            self.width * (1.0 - 0.5 * self.heat),
+            WHITE,
+        );
+    }
+}
+
+/// The part of an entity a beam stopped at.
+#[derive(Clone, Copy, Debug)]
+enum BeamHit {
+    Shield,
+    Armor(ArmorSlot),
+}
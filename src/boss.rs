@@ -0,0 +1,157 @@
+use macroquad::{prelude::*, rand};
+use nalgebra::{Point2, UnitComplex, vector};
+
+use crate::{
+    app::App,
+    computer_controller::{ComputerMotionControllerKind, Weapon},
+    controller::{MotionController, ShootingController},
+    entity::Entity,
+};
+
+/// Changes made to a boss when it enters a phase. Anything left as `None` carries over from the
+/// previous phase.
+#[derive(Clone, Debug)]
+pub struct Phase {
+    pub motion: Option<ComputerMotionControllerKind>,
+    pub weapon: Option<Weapon>,
+    /// Multiplies the target speed of every remaining ring
+    pub ring_speed_multiplier: f32,
+    /// Archetypes spawned around the boss, joining its team and fight
+    pub minions: Vec<fn(Point2<f32>) -> Entity>,
+}
+
+impl Default for Phase {
+    fn default() -> Self {
+        Self {
+            motion: None,
+            weapon: None,
+            ring_speed_multiplier: 1.0,
+            minions: Vec::new(),
+        }
+    }
+}
+
+/// Moves an entity through `phases` as its rings are destroyed. The first phase starts once the
+/// first ring is gone, the second once the second is gone, and so on.
+#[derive(Clone, Debug)]
+pub struct Boss {
+    pub name: &'static str,
+    pub phases: Vec<Phase>,
+    /// Number of phases entered so far
+    pub phase: usize,
+    /// Ring count the boss started with
+    pub rings: usize,
+    /// Combined health of every ring piece and the center at full strength
+    pub max_health: u32,
+}
+
+impl Boss {
+    /// Fraction of the screen width
+    pub const HEALTH_BAR_WIDTH: f32 = 0.5;
+    pub const HEALTH_BAR_HEIGHT: f32 = 12.0;
+
+    pub fn from_phases(name: &'static str, entity: &Entity, phases: Vec<Phase>) -> Self {
+        let max_health = entity
+            .rings
+            .iter()
+            .flat_map(|ring| &ring.blueprints)
+            .chain(entity.center.armor.as_ref())
+            .map(|armor| armor.max_health.get() as u32)
+            .sum();
+
+        Self {
+            name,
+            phases,
+            phase: 0,
+            rings: entity.rings.len(),
+            max_health,
+        }
+    }
+
+    /// Enters every phase whose ring has been destroyed since the last update. Rings that
+    /// regenerate are kept around empty rather than removed, so those count as destroyed too.
+    pub fn update(&mut self, entity: &mut Entity, app: &mut App) {
+        let emptied = entity
+            .rings
+            .iter()
+            .filter(|ring| ring.armor.iter().all(Option::is_none))
+            .count();
+        let destroyed = self.rings.saturating_sub(entity.rings.len()) + emptied;
+        while self.phase < destroyed.min(self.phases.len()) {
+            self.phases[self.phase].enter(entity, app);
+            self.phase += 1;
+        }
+    }
+
+    pub fn get_health(entity: &Entity) -> u32 {
+        entity
+            .rings
+            .iter()
+            .flat_map(|ring| ring.armor.iter().flatten())
+            .chain(entity.center.armor.as_ref())
+            .map(|armor| armor.health.get() as u32)
+            .sum()
+    }
+
+    /// Draws the health bar across the top of the screen. This switches to the screen space camera.
+    pub fn draw_health_bar(&self, entity: &Entity, color: Color) {
+        set_default_camera();
+
+        let width = screen_width() * Self::HEALTH_BAR_WIDTH;
+        let x = (screen_width() - width) / 2.0;
+        let y = Self::HEALTH_BAR_HEIGHT * 2.0;
+        let fraction = Self::get_health(entity) as f32 / self.max_health as f32;
+
+        draw_rectangle(
+            x,
+            y,
+            width,
+            Self::HEALTH_BAR_HEIGHT,
+            Color { a: 0.25, ..color },
+        );
+        draw_rectangle(x, y, width * fraction, Self::HEALTH_BAR_HEIGHT, color);
+        draw_rectangle_lines(x, y, width, Self::HEALTH_BAR_HEIGHT, 2.0, color);
+        draw_text(self.name, x, y - 4.0, Self::HEALTH_BAR_HEIGHT * 1.5, WHITE);
+    }
+}
+
+impl Phase {
+    pub const MINION_SPAWN_DISTANCE: f32 = 4.0;
+
+    pub fn enter(&self, entity: &mut Entity, app: &mut App) {
+        for ring in &mut entity.rings {
+            ring.target_speed *= self.ring_speed_multiplier;
+        }
+
+        let Some(controller) = &mut entity.controller else {
+            return;
+        };
+
+        if let (Some(kind), Some(MotionController::Computer(motion))) =
+            (&self.motion, &mut controller.motion)
+        {
+            motion.kind = kind.clone();
+        }
+        if let (Some(weapon), Some(ShootingController::Computer(shooting))) =
+            (&self.weapon, &mut controller.shooting)
+        {
+            shooting.weapon = weapon.clone();
+            shooting.cooldown = 0.0;
+        }
+
+        for minion in &self.minions {
+            let direction = UnitComplex::new(rand::gen_range(0.0, std::f32::consts::TAU));
+            let position = entity.position
+                + direction * vector![entity.radius + Self::MINION_SPAWN_DISTANCE, 0.0];
+
+            let mut minion = minion(position);
+            minion.team = entity.team;
+            minion.color = entity.color;
+            minion.velocity = entity.velocity;
+            if let Some(minion_controller) = &mut minion.controller {
+                minion_controller.targets = controller.targets.clone();
+            }
+            app.spawns.push(minion);
+        }
+    }
+}
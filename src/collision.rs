@@ -1,5 +1,6 @@
 use macroquad::prelude::*;
 use nalgebra::{center, distance_squared, vector, Point2, UnitComplex, Vector2};
+use std::collections::HashMap;
 
 /// Corners are assumed to be in either clockwise or counter-clockwise order.
 #[derive(Clone, Debug)]
@@ -56,6 +57,29 @@ impl Rectangle {
         }
     }
 
+    /// Returns the distance along the ray to the closest edge it crosses.
+    pub fn ray_distance(&self, origin: Point2<f32>, direction: Vector2<f32>) -> Option<f32> {
+        let cross = |a: Vector2<f32>, b: Vector2<f32>| a.x * b.y - a.y * b.x;
+
+        loop_indices::<4>()
+            .into_iter()
+            .filter_map(|(a, b)| {
+                let edge = self.corners[b] - self.corners[a];
+                let to_corner = self.corners[a] - origin;
+
+                let denominator = cross(direction, edge);
+                if denominator == 0.0 {
+                    return None;
+                }
+
+                let distance = cross(to_corner, edge) / denominator;
+                let along_edge = cross(to_corner, direction) / denominator;
+
+                (distance >= 0.0 && (0.0..=1.0).contains(&along_edge)).then_some(distance)
+            })
+            .min_by(|a, b| a.partial_cmp(b).unwrap())
+    }
+
     pub fn radius_squared(&self) -> f32 {
         distance_squared(&self.corners[0], &self.corners[2]) / 4.0
     }
@@ -65,6 +89,55 @@ impl Rectangle {
     }
 }
 
+/// Uniform grid used as a broadphase, so only nearby items need to be checked against each other.
+#[derive(Clone, Debug)]
+pub struct SpatialGrid<T> {
+    pub cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<T>>,
+}
+
+impl<T: Copy> SpatialGrid<T> {
+    pub fn from_cell_size(cell_size: f32) -> Self {
+        let cells = HashMap::new();
+        Self { cell_size, cells }
+    }
+
+    /// Empties the grid while keeping the allocations of cells that were in use.
+    pub fn clear(&mut self) {
+        self.cells.retain(|_, cell| {
+            let in_use = !cell.is_empty();
+            cell.clear();
+            in_use
+        });
+    }
+
+    pub fn insert(&mut self, position: Point2<f32>, item: T) {
+        self.cells
+            .entry(self.get_cell(position))
+            .or_default()
+            .push(item);
+    }
+
+    /// Returns every item in a cell overlapping the square around `position`.
+    pub fn query(&self, position: Point2<f32>, radius: f32) -> impl Iterator<Item = T> + '_ {
+        let min = self.get_cell(position - vector![radius, radius]);
+        let max = self.get_cell(position + vector![radius, radius]);
+
+        (min.0..=max.0)
+            .flat_map(move |x| (min.1..=max.1).map(move |y| (x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+    }
+
+    fn get_cell(&self, position: Point2<f32>) -> (i32, i32) {
+        (
+            (position.x / self.cell_size).floor() as i32,
+            (position.y / self.cell_size).floor() as i32,
+        )
+    }
+}
+
 /// For use with array::map.
 /// It is rarely nececcary to specify the length of the output.
 /// ```
@@ -72,8 +145,8 @@ impl Rectangle {
 /// ```
 fn loop_indices<const N: usize>() -> [(usize, usize); N] {
     let mut array = [Default::default(); N];
-    for i in 0..N {
-        array[i] = (i, i + 1);
+    for (i, pair) in array.iter_mut().enumerate() {
+        *pair = (i, i + 1);
     }
     array[N - 1].1 = 0;
     array
@@ -1,119 +1,373 @@
-use crate::collision::Rectangle;
+use crate::{
+    collision::Rectangle,
+    damage::{Damage, DamageKind, Resistances},
+    mount::Mount,
+    util,
+};
 use macroquad::prelude::*;
-use nalgebra::{vector, Point2, UnitComplex, Vector2};
-use std::num::NonZeroU8;
+use nalgebra::{Point2, UnitComplex, Vector2, vector};
+use std::{num::NonZeroU16, ops::Range};
 
 #[derive(Clone, Debug)]
 pub struct ArmorRing {
     pub armor: Vec<Option<Armor>>,
+    /// The armor each slot starts with, used to regrow destroyed pieces
+    pub blueprints: Vec<Armor>,
+    /// Angle of each slot relative to `angle`. Slots don't have to be evenly spaced, and gaps
+    /// are simply angles without a slot.
+    pub offsets: Vec<f32>,
     pub radius: f32,
+    pub orbit: Orbit,
     pub angle: f32,
+    /// Current angular velocity, which accelerates towards the commanded speed
     pub speed: f32,
+    pub target_speed: f32,
+    pub angular_acceleration: f32,
+    /// Holds the ring still without forgetting `target_speed`
+    pub locked: bool,
+    pub regeneration: Option<Regeneration>,
+    pub mounts: Vec<Mount>,
 }
 
 impl ArmorRing {
+    pub const DEFAULT_ANGULAR_ACCELERATION: f32 = std::f32::consts::PI;
+
     pub fn from_size(
         size: Vector2<f32>,
-        health: u8,
+        health: u16,
         count: usize,
         radius: f32,
         speed: f32,
     ) -> Self {
-        let health = NonZeroU8::new(health).unwrap();
-        let armor = (0..count)
-            .map(|_| Some(Armor::from_size(size, health)))
-            .collect();
+        use std::f32::consts::TAU;
+        let health = NonZeroU16::new(health).unwrap();
+        let blueprints = vec![Armor::from_size(size, health); count];
+        let offsets = (0..count).map(|i| TAU / count as f32 * i as f32).collect();
+        Self::from_blueprints(blueprints, offsets, radius, speed)
+    }
+
+    /// Builds a ring slot by slot, leaving gaps wherever no slot covers.
+    pub fn from_slots(slots: &[RingSlot], radius: f32, speed: f32) -> Self {
+        let blueprints = slots.iter().map(|slot| slot.get_armor(radius)).collect();
+        let offsets = slots.iter().map(|slot| slot.offset).collect();
+        Self::from_blueprints(blueprints, offsets, radius, speed)
+    }
+
+    fn from_blueprints(blueprints: Vec<Armor>, offsets: Vec<f32>, radius: f32, speed: f32) -> Self {
+        let armor = blueprints.iter().copied().map(Some).collect();
         let angle = 0.0;
         Self {
             armor,
+            blueprints,
+            offsets,
             radius,
+            orbit: Default::default(),
             angle,
             speed,
+            target_speed: speed,
+            angular_acceleration: Self::DEFAULT_ANGULAR_ACCELERATION,
+            locked: false,
+            regeneration: None,
+            mounts: Vec::new(),
         }
     }
 
+    pub fn with_resistances(mut self, resistances: Resistances) -> Self {
+        for armor in self.armor.iter_mut().flatten().chain(&mut self.blueprints) {
+            armor.resistances = resistances;
+        }
+        self
+    }
+
+    pub fn with_regeneration(mut self, regeneration: Regeneration) -> Self {
+        self.regeneration = Some(regeneration);
+        self
+    }
+
+    pub fn with_orbit(mut self, orbit: Orbit) -> Self {
+        self.orbit = orbit;
+        self
+    }
+
+    pub fn with_mount(mut self, mount: Mount) -> Self {
+        self.mounts.push(mount);
+        self
+    }
+
     pub fn draw_around(&self, position: Point2<f32>, color: Color) {
-        let mut angle = self.angle;
-        let increment = self.get_increment();
-
-        for armor in &self.armor {
-            if let Some(armor) = armor {
-                let angle_complex = UnitComplex::new(angle);
-                draw_rectangle_ex(
-                    position.x + self.radius * angle_complex.re,
-                    position.y + self.radius * angle_complex.im,
+        let rebuilding = self
+            .regeneration
+            .and_then(|regeneration| regeneration.rebuilding);
+
+        for (i, armor) in self.armor.iter().enumerate() {
+            let (base, direction) = self.get_slot_transform(i);
+            let (length, width, color) = match (armor, rebuilding) {
+                (Some(armor), _) => (
                     (armor.get_health_ratio() * armor.size.y).max(0.125),
                     armor.size.x,
-                    DrawRectangleParams {
-                        offset: vec2(0.0, 0.5),
-                        rotation: angle,
-                        color: armor.modify_color(color),
-                    },
-                );
-            }
-            angle += increment;
+                    armor.modify_color(color),
+                ),
+                // Pieces being rebuilt grow out as a faint ghost of the original
+                (None, Some((slot, progress))) if slot == i => (
+                    progress * self.blueprints[i].size.y,
+                    self.blueprints[i].size.x,
+                    Color { a: 0.375, ..color },
+                ),
+                (None, _) => continue,
+            };
+
+            draw_rectangle_ex(
+                position.x + base.x,
+                position.y + base.y,
+                length,
+                width,
+                DrawRectangleParams {
+                    offset: vec2(0.0, 0.5),
+                    rotation: direction.angle(),
+                    color,
+                },
+            );
+        }
+
+        for mount in &self.mounts {
+            let Some(armor) = self.armor[mount.slot] else {
+                continue;
+            };
+
+            let (base, direction) = self.get_slot_transform(mount.slot);
+            let tip = position + base + direction * vector![armor.size.y, 0.0];
+            mount.draw(tip, direction.angle(), color);
         }
     }
 
     pub fn update(&mut self, delta_seconds: f32) {
+        for armor in self.armor.iter_mut().flatten() {
+            armor.update_hit_effect(delta_seconds);
+        }
+    }
+
+    /// Spins the ring towards its commanded speed and moves it along its orbit. Skipped while the
+    /// ring is jammed, which stops it dead.
+    pub fn rotate(&mut self, delta_seconds: f32) {
         use std::f32::consts::TAU;
+        let max_change = self.angular_acceleration * delta_seconds;
+        self.speed += (self.get_commanded_speed() - self.speed).clamp(-max_change, max_change);
+
         self.angle += self.speed * delta_seconds;
         self.angle %= TAU;
+        self.orbit.update(delta_seconds);
+    }
 
-        for armor in &mut self.armor {
-            if let Some(armor) = armor {
-                armor.update_hit_effect(delta_seconds);
-            }
+    pub fn get_commanded_speed(&self) -> f32 {
+        if self.locked { 0.0 } else { self.target_speed }
+    }
+
+    /// Heals damaged pieces and regrows destroyed slots one at a time once the ring hasn't been hit
+    /// for a while. Returns `true` if a slot regrew, which changes the shape of the entity.
+    pub fn regenerate(&mut self, delta_seconds: f32) -> bool {
+        let Some(regeneration) = &mut self.regeneration else {
+            return false;
+        };
+        let Some(healing) = regeneration.update(delta_seconds) else {
+            return false;
+        };
+
+        for armor in self.armor.iter_mut().flatten() {
+            armor.heal(healing);
         }
+
+        let (slot, progress) = match regeneration.rebuilding {
+            Some(rebuilding) => rebuilding,
+            None => match self.armor.iter().position(Option::is_none) {
+                Some(slot) => (slot, 0.0),
+                None => return false,
+            },
+        };
+
+        let progress = progress + delta_seconds / regeneration.rebuild_time;
+        if progress < 1.0 {
+            regeneration.rebuilding = Some((slot, progress));
+            return false;
+        }
+
+        regeneration.rebuilding = None;
+        self.regrow(slot, NonZeroU16::MIN);
+
+        true
     }
 
+    pub fn regrow(&mut self, slot: usize, health: NonZeroU16) {
+        if let Some(regeneration) = &mut self.regeneration
+            && regeneration
+                .rebuilding
+                .is_some_and(|(rebuilding, _)| rebuilding == slot)
+        {
+            regeneration.rebuilding = None;
+        }
+
+        self.armor[slot] = Some(Armor {
+            health,
+            ..self.blueprints[slot]
+        });
+    }
+
+    /// Covers the farthest the pieces can ever reach along the orbit, not just where they are now.
     pub fn get_full_radius_squared(&self) -> Option<f32> {
-        (&self.armor)
-            .into_iter()
+        let max_distance = self.orbit.get_max_distance(self.radius);
+        self.armor
+            .iter()
             .filter_map(|&a| a)
-            .map(|a| a.get_radius_squared(self.radius))
+            .map(|a| a.get_radius_squared(max_distance))
             .max_by(|x, y| x.partial_cmp(y).unwrap())
     }
 
-    pub fn get_increment(&self) -> f32 {
-        use std::f32::consts::TAU;
-        TAU / self.armor.len() as f32
-    }
-
-    pub fn get_colliders(&self, position: Point2<f32>) -> Vec<Option<Rectangle>> {
-        let increment = self.get_increment();
-        (&self.armor)
-            .into_iter()
-            .enumerate()
-            .map(|(i, armor)| match armor {
-                Some(armor) => {
-                    let angle = UnitComplex::new(increment * i as f32 + self.angle);
-                    Some(Rectangle::from_dimensions(
-                        position + angle * vector![self.radius, 0.0],
-                        vector![armor.get_health_ratio() * armor.size.y, armor.size.x],
-                        vector![0.0, 0.5],
-                        angle,
-                    ))
-                }
-                None => None,
-            })
-            .collect()
+    pub fn get_slot_angle(&self, slot: usize) -> f32 {
+        self.angle + self.offsets[slot]
+    }
+
+    /// Returns where the inner end of the piece in `slot` sits relative to the entity position,
+    /// along with the direction the piece points in.
+    pub fn get_slot_transform(&self, slot: usize) -> (Vector2<f32>, UnitComplex<f32>) {
+        self.orbit.get_point(self.get_slot_angle(slot), self.radius)
     }
 
-    /// Returns a vector of colliders associated with their armor piece.
-    pub fn get_colliders_zip(
-        &mut self,
+    pub fn push_colliders(
+        &self,
+        ring_index: usize,
         position: Point2<f32>,
-    ) -> Vec<(Rectangle, &mut Option<Armor>)> {
-        self.get_colliders(position)
-            .into_iter()
-            .zip(&mut *self.armor)
-            .map(|(r, a)| Some((r?, a)))
-            .filter_map(|a| a)
-            .collect()
+        colliders: &mut Vec<(Rectangle, ArmorSlot)>,
+    ) {
+        for (i, armor) in self.armor.iter().enumerate() {
+            let Some(armor) = armor else {
+                continue;
+            };
+
+            let (base, direction) = self.get_slot_transform(i);
+            colliders.push((
+                Rectangle::from_dimensions(
+                    position + base,
+                    vector![armor.get_health_ratio() * armor.size.y, armor.size.x],
+                    vector![0.0, 0.5],
+                    direction,
+                ),
+                ArmorSlot::Ring {
+                    ring: ring_index,
+                    slot: i,
+                },
+            ));
+        }
+    }
+
+    /// The range of distances from the entity position that the armor pieces can cover.
+    pub fn get_band(&self) -> Range<f32> {
+        // The inner corners of a piece can sit closer in than the orbit itself
+        let half_width = self
+            .armor
+            .iter()
+            .flatten()
+            .map(|armor| armor.size.x / 2.0)
+            .fold(0.0, f32::max);
+        let start = (self.orbit.get_min_distance(self.radius) - half_width).max(0.0);
+
+        start..self.get_full_radius_squared().unwrap_or(0.0).sqrt()
+    }
+}
+
+/// The path the pieces of a ring follow around the entity. The default is a circle of the ring
+/// radius centered on the entity.
+#[derive(Clone, Copy, Debug)]
+pub struct Orbit {
+    /// Ratio of the second axis to the ring radius, 1.0 for a circle
+    pub aspect: f32,
+    pub tilt: f32,
+    /// How far the orbit grows and shrinks, as a fraction of the ring radius
+    pub breathing: f32,
+    pub breathing_speed: f32,
+    pub breathing_phase: f32,
+    /// Center of the orbit relative to the entity position
+    pub offset: Vector2<f32>,
+}
+
+impl Orbit {
+    pub fn update(&mut self, delta_seconds: f32) {
+        use std::f32::consts::TAU;
+        self.breathing_phase += self.breathing_speed * delta_seconds;
+        self.breathing_phase %= TAU;
+    }
+
+    /// Returns the point at `angle` along the orbit relative to the entity position, along with
+    /// the outward normal of the path there.
+    pub fn get_point(&self, angle: f32, radius: f32) -> (Vector2<f32>, UnitComplex<f32>) {
+        let scale = radius * (1.0 + self.breathing * self.breathing_phase.sin());
+        let (a, b) = (scale, scale * self.aspect);
+        let tilt = UnitComplex::new(self.tilt);
+        let (sin, cos) = (angle - self.tilt).sin_cos();
+
+        let point = self.offset + tilt * vector![a * cos, b * sin];
+        let normal = tilt * vector![cos * b, sin * a];
+
+        (point, UnitComplex::new(normal.y.atan2(normal.x)))
+    }
+
+    pub fn get_max_distance(&self, radius: f32) -> f32 {
+        util::length(self.offset) + radius * self.aspect.max(1.0) * (1.0 + self.breathing.abs())
+    }
+
+    pub fn get_min_distance(&self, radius: f32) -> f32 {
+        let closest = radius * self.aspect.min(1.0) * (1.0 - self.breathing.abs());
+        (closest - util::length(self.offset)).max(0.0)
     }
 }
 
+impl Default for Orbit {
+    fn default() -> Self {
+        Self {
+            aspect: 1.0,
+            tilt: 0.0,
+            breathing: 0.0,
+            breathing_speed: 0.0,
+            breathing_phase: 0.0,
+            offset: Vector2::zeros(),
+        }
+    }
+}
+
+/// One slot of a ring built with `ArmorRing::from_slots`.
+#[derive(Clone, Copy, Debug)]
+pub struct RingSlot {
+    /// Angle of the middle of the slot relative to the ring
+    pub offset: f32,
+    /// Angle covered by the inner edge of the piece
+    pub angular_width: f32,
+    pub length: f32,
+    pub health: u16,
+}
+
+impl RingSlot {
+    pub fn from_width(offset: f32, angular_width: f32, length: f32, health: u16) -> Self {
+        Self {
+            offset,
+            angular_width,
+            length,
+            health,
+        }
+    }
+
+    pub fn get_armor(&self, radius: f32) -> Armor {
+        let width = 2.0 * radius * (self.angular_width / 2.0).sin();
+        Armor::from_size(
+            vector![width, self.length],
+            NonZeroU16::new(self.health).unwrap(),
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArmorSlot {
+    Center,
+    Ring { ring: usize, slot: usize },
+}
+
 /// Note that accessing `size` or `health` will panic if `armor` is `None`. This should never be
 /// the case unless the entity center is associated with is about to be deleted.
 #[derive(Clone, Copy, Debug)]
@@ -121,20 +375,32 @@ pub struct Center {
     pub armor: Option<Armor>,
     pub angle: f32,
     pub speed: f32,
+    pub regeneration: Option<Regeneration>,
 }
 
 impl Center {
-    pub fn from_size(size: Vector2<f32>, health: u8, speed: f32) -> Self {
-        let health = NonZeroU8::new(health).unwrap();
+    pub fn from_size(size: Vector2<f32>, health: u16, speed: f32) -> Self {
+        let health = NonZeroU16::new(health).unwrap();
         let armor = Some(Armor::from_size(size, health));
         let angle = 0.0;
         Self {
             armor,
             angle,
             speed,
+            regeneration: None,
         }
     }
 
+    pub fn with_resistances(mut self, resistances: Resistances) -> Self {
+        self.resistances = resistances;
+        self
+    }
+
+    pub fn with_regeneration(mut self, regeneration: Regeneration) -> Self {
+        self.regeneration = Some(regeneration);
+        self
+    }
+
     pub fn draw_around(&self, position: Point2<f32>, color: Color) {
         draw_rectangle_ex(
             position.x,
@@ -148,24 +414,24 @@ impl Center {
             },
         );
 
-        if let Some(armor) = self.armor {
-            if armor.health.get() < armor.max_health.get() {
-                let health_proportion = armor.health.get() as f32 / armor.max_health.get() as f32;
-
-                let hole_size = self.size * (health_proportion - 1.0) * 0.8;
-
-                draw_rectangle_ex(
-                    position.x,
-                    position.y,
-                    hole_size.x,
-                    hole_size.y,
-                    DrawRectangleParams {
-                        offset: vec2(0.5, 0.5),
-                        rotation: self.angle,
-                        color: Color::from_hex(0x000000),
-                    },
-                );
-            }
+        if let Some(armor) = self.armor
+            && armor.health.get() < armor.max_health.get()
+        {
+            let health_proportion = armor.health.get() as f32 / armor.max_health.get() as f32;
+
+            let hole_size = self.size * (health_proportion - 1.0) * 0.8;
+
+            draw_rectangle_ex(
+                position.x,
+                position.y,
+                hole_size.x,
+                hole_size.y,
+                DrawRectangleParams {
+                    offset: vec2(0.5, 0.5),
+                    rotation: self.angle,
+                    color: Color::from_hex(0x000000),
+                },
+            );
         }
     }
 
@@ -176,6 +442,21 @@ impl Center {
         self.update_hit_effect(delta_seconds);
     }
 
+    /// Heals the center once it hasn't been hit for a while. A destroyed center can't regrow.
+    pub fn regenerate(&mut self, delta_seconds: f32) {
+        if self.armor.is_none() {
+            return;
+        }
+
+        if let Some(healing) = self
+            .regeneration
+            .as_mut()
+            .and_then(|regeneration| regeneration.update(delta_seconds))
+        {
+            self.heal(healing);
+        }
+    }
+
     pub fn get_radius_squared(&self) -> f32 {
         (self.size.x * self.size.x + self.size.y * self.size.y) / 4.0
     }
@@ -207,38 +488,45 @@ impl std::ops::DerefMut for Center {
 #[derive(Clone, Copy, Debug)]
 pub struct Armor {
     pub size: Vector2<f32>,
-    pub health: NonZeroU8,
-    pub max_health: NonZeroU8,
+    pub health: NonZeroU16,
+    pub max_health: NonZeroU16,
+    pub resistances: Resistances,
     pub hit_effect: u16,
 }
 
 impl Armor {
-    pub fn from_size(size: Vector2<f32>, health: NonZeroU8) -> Self {
+    pub fn from_size(size: Vector2<f32>, health: NonZeroU16) -> Self {
         let hit_effect = 0;
         let max_health = health;
+        let resistances = Resistances::NONE;
         Self {
             size,
             health,
             max_health,
+            resistances,
             hit_effect,
         }
     }
 
-    pub fn damage(reference: &mut Option<Armor>, damage: u8) {
+    pub fn damage(reference: &mut Option<Armor>, damage: Damage) {
         if let Some(armor) = reference {
-            match NonZeroU8::new(armor.health.get().saturating_sub(damage)) {
+            let damage = armor.resistances.apply(damage);
+            match NonZeroU16::new(armor.health.get().saturating_sub(damage)) {
                 Some(health) => {
                     armor.health = health;
                     armor.hit_effect = u16::MAX / 4 * 3;
                 }
                 None => {
                     *reference = None;
-                    return;
                 }
             }
         }
     }
 
+    pub fn heal(&mut self, amount: u16) {
+        self.health = self.health.saturating_add(amount).min(self.max_health);
+    }
+
     pub fn get_radius_squared(&self, radius: f32) -> f32 {
         let height = self.size.y + radius;
         height * height + self.size.x * self.size.x / 4.0
@@ -263,3 +551,130 @@ impl Armor {
         self.health.get() as f32 / self.max_health.get() as f32
     }
 }
+
+/// Heals armor that hasn't been hit for `delay` seconds.
+#[derive(Clone, Copy, Debug)]
+pub struct Regeneration {
+    pub delay: f32,
+    /// Health restored to every damaged piece per second
+    pub rate: f32,
+    pub rebuild_time: f32,
+    pub since_hit: f32,
+    pub accumulated_healing: f32,
+    /// The destroyed slot currently regrowing, along with its progress from 0.0 to 1.0
+    pub rebuilding: Option<(usize, f32)>,
+}
+
+impl Regeneration {
+    pub fn from_rate(delay: f32, rate: f32, rebuild_time: f32) -> Self {
+        Self {
+            delay,
+            rate,
+            rebuild_time,
+            since_hit: 0.0,
+            accumulated_healing: 0.0,
+            rebuilding: None,
+        }
+    }
+
+    /// Returns the health to restore this update, or `None` while still waiting out the delay.
+    pub fn update(&mut self, delta_seconds: f32) -> Option<u16> {
+        self.since_hit += delta_seconds;
+        if self.since_hit < self.delay {
+            return None;
+        }
+
+        self.accumulated_healing += self.rate * delta_seconds;
+        let amount = self.accumulated_healing.floor();
+        self.accumulated_healing -= amount;
+
+        Some(amount as u16)
+    }
+
+    /// Restarts the delay and throws away any progress on rebuilding.
+    pub fn interrupt(&mut self) {
+        self.since_hit = 0.0;
+        self.accumulated_healing = 0.0;
+        self.rebuilding = None;
+    }
+}
+
+/// A circular barrier just outside the outermost ring that absorbs hits before they reach the
+/// armor.
+#[derive(Clone, Debug)]
+pub struct Shield {
+    pub capacity: f32,
+    pub charge: f32,
+    pub recharge_rate: f32,
+    pub recharge_delay: f32,
+    pub since_hit: f32,
+    pub margin: f32,
+    /// Multipliers applied to the charge drained by each damage kind. Above 1.0 overloads the
+    /// shield.
+    pub resistances: Resistances,
+    /// Damage kinds that pass straight through to the armor
+    pub bypass: Vec<DamageKind>,
+    pub hit_effect: u16,
+}
+
+impl Shield {
+    pub fn from_capacity(capacity: f32, recharge_rate: f32, recharge_delay: f32) -> Self {
+        Self {
+            capacity,
+            charge: capacity,
+            recharge_rate,
+            recharge_delay,
+            since_hit: 0.0,
+            margin: 1.5,
+            resistances: Resistances::NONE,
+            bypass: Vec::new(),
+            hit_effect: 0,
+        }
+    }
+
+    pub fn is_up(&self) -> bool {
+        self.charge > 0.0
+    }
+
+    pub fn blocks(&self, kind: DamageKind) -> bool {
+        self.is_up() && !self.bypass.contains(&kind)
+    }
+
+    /// Drains charge for the damage. The whole hit is absorbed even if it empties the shield.
+    pub fn absorb(&mut self, damage: Damage) {
+        let drain = damage.amount as f32 * self.resistances.multiplier(damage.kind);
+        self.charge = (self.charge - drain).max(0.0);
+        self.since_hit = 0.0;
+        self.hit_effect = u16::MAX / 4 * 3;
+    }
+
+    pub fn update(&mut self, delta_seconds: f32) {
+        self.hit_effect =
+            (self.hit_effect).saturating_sub((2.0 * delta_seconds * u16::MAX as f32) as u16);
+
+        self.since_hit += delta_seconds;
+        if self.since_hit >= self.recharge_delay {
+            self.charge = (self.charge + self.recharge_rate * delta_seconds).min(self.capacity);
+        }
+    }
+
+    pub fn draw_around(&self, position: Point2<f32>, radius: f32, color: Color) {
+        if !self.is_up() {
+            return;
+        }
+
+        let multiplier = 1.0 - self.hit_effect as f32 / u16::MAX as f32;
+        draw_circle_lines(
+            position.x,
+            position.y,
+            radius,
+            0.25 + 0.25 * self.charge / self.capacity,
+            Color {
+                r: color.r * multiplier,
+                g: color.g * multiplier,
+                b: color.b * multiplier,
+                a: 0.5,
+            },
+        );
+    }
+}
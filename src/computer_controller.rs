@@ -1,10 +1,21 @@
-use std::ops::Range;
+use std::{f32::consts::TAU, ops::Range};
 
-use macroquad::rand;
+use macroquad::{color::Color, rand};
 use nalgebra::{Complex, Point2, UnitComplex, Vector2, vector};
 use thunderdome::Index;
 
-use crate::{app::App, controller::SightKind, entity::Entity, util};
+use crate::{
+    app::App,
+    beam::Beam,
+    controller::SightKind,
+    damage::{Damage, DamageKind},
+    entity::Entity,
+    guidance::Guidance,
+    particle::Emitter,
+    projectile::Interception,
+    status::StatusEffect,
+    util,
+};
 
 #[derive(Clone, Debug)]
 pub struct ComputerMotionController {
@@ -13,11 +24,17 @@ pub struct ComputerMotionController {
 }
 
 impl ComputerMotionController {
-    pub fn update(&mut self, entity: &mut Entity, targets: &mut Vec<Index>, app: &mut App) {
+    pub fn update(
+        &mut self,
+        entity: &mut Entity,
+        targets: &mut [Index],
+        delta_seconds: f32,
+        app: &mut App,
+    ) {
         let Some((target_index, displacement, distance_squared)) =
             closest_target(targets.iter(), entity.position, app)
         else {
-            entity.velocity = [0.0; 2].into();
+            entity.thrust_towards([0.0; 2].into(), delta_seconds);
 
             return;
         };
@@ -27,7 +44,7 @@ impl ComputerMotionController {
             distance_to_target - app.entities[target_index].radius - entity.radius;
         let direction = displacement / distance_to_target;
 
-        match self.kind {
+        let velocity = match self.kind {
             ComputerMotionControllerKind::KeepDistance {
                 distance:
                     Range {
@@ -36,11 +53,11 @@ impl ComputerMotionController {
                     },
             } => {
                 if distance_to_target_edge < min_distance {
-                    entity.velocity = self.speed * -direction;
+                    self.speed * -direction
                 } else if distance_to_target_edge > max_distance {
-                    entity.velocity = self.speed * direction;
+                    self.speed * direction
                 } else {
-                    entity.velocity = vector![0.0, 0.0];
+                    vector![0.0, 0.0]
                 }
             }
             ComputerMotionControllerKind::Circle {
@@ -50,12 +67,12 @@ impl ComputerMotionController {
                 let perpendicular = tangential_weight * vector![-direction.y, direction.x];
                 let radial = direction * (distance_to_target_edge - distance);
 
-                entity.velocity = self.speed * (perpendicular + radial).normalize();
-            }
-            ComputerMotionControllerKind::Charge => {
-                entity.velocity = self.speed * direction;
+                self.speed * (perpendicular + radial).normalize()
             }
-        }
+            ComputerMotionControllerKind::Charge => self.speed * direction,
+        };
+
+        entity.thrust_towards(velocity, delta_seconds);
     }
 }
 
@@ -74,6 +91,8 @@ pub enum ComputerMotionControllerKind {
 #[derive(Clone, Debug)]
 pub struct ComputerShootingController {
     pub weapon: Weapon,
+    /// Fired instead of `weapon` while the target is within range if this is set
+    pub beam: Option<Beam>,
     pub aim: Option<UnitComplex<f32>>,
     pub cooldown: f32,
     /// 1.0 means use leading, 0.0 means ignore leading
@@ -81,6 +100,8 @@ pub struct ComputerShootingController {
     /// this is added to the muzzle distance when calculating target leading
     /// 10.0 typically leads to best performance
     pub lead_weight: f32,
+    pub aim_kind: ComputerAimKind,
+    pub firing_kind: ComputerFiringKind,
 }
 
 impl ComputerShootingController {
@@ -88,7 +109,7 @@ impl ComputerShootingController {
         &mut self,
         index: Index,
         entity: &mut Entity,
-        targets: &mut Vec<Index>,
+        targets: &mut [Index],
         delta_seconds: f32,
         app: &mut App,
     ) {
@@ -99,6 +120,10 @@ impl ComputerShootingController {
         else {
             self.aim = None;
 
+            if let Some(beam) = &mut self.beam {
+                beam.update(None, index, entity, delta_seconds, app);
+            }
+
             return;
         };
 
@@ -106,7 +131,12 @@ impl ComputerShootingController {
 
         let muzzle_length = entity.radius + 4.0;
 
-        let lead_amount = if self.aiming_lead != 0.0 {
+        let aiming_lead = match self.aim_kind {
+            ComputerAimKind::Predictive => 1.0,
+            _ => self.aiming_lead,
+        };
+
+        let lead_amount = if aiming_lead != 0.0 {
             let muzzle_distance = distance_squared.sqrt() + self.lead_weight - muzzle_length;
 
             let expected_time_to_target = if self.weapon.speed_exponent == 1.0 {
@@ -127,68 +157,137 @@ impl ComputerShootingController {
                     .log(self.weapon.speed_exponent)
             };
 
-            expected_time_to_target * target.velocity * self.aiming_lead
+            expected_time_to_target * target.velocity * aiming_lead
         } else {
             [0.0; 2].into()
         };
 
         let aim = displacement + lead_amount;
-
         let aim = UnitComplex::from_complex(Complex::new(aim.x, aim.y));
-        self.aim = Some(aim);
 
-        if self.cooldown <= 0.0 {
-            self.cooldown = self.weapon.cooldown;
+        let aim = match &mut self.aim_kind {
+            ComputerAimKind::PointTowards | ComputerAimKind::Predictive => aim,
+            ComputerAimKind::Sweep {
+                amplitude,
+                speed,
+                phase,
+            } => {
+                *phase = (*phase + *speed * delta_seconds) % TAU;
+                UnitComplex::new(*amplitude * phase.sin()) * aim
+            }
+            ComputerAimKind::Fixed { angle } => UnitComplex::new(*angle),
+        };
+        self.aim = Some(aim);
 
-            let aim_angle = aim.angle();
-            let projectile_sweep =
-                (self.weapon.projectiles_per_shot - 1) as f32 * self.weapon.projectile_angle;
+        let distance = distance_squared.sqrt();
+        let allowed = match &self.firing_kind {
+            ComputerFiringKind::Always | ComputerFiringKind::Burst { .. } => true,
+            ComputerFiringKind::WithinDistance { distance: range } => {
+                range.contains(&(distance - target.radius - entity.radius))
+            }
+            ComputerFiringKind::ClearLine => {
+                let direction = UnitComplex::new(displacement.y.atan2(displacement.x));
+                let blocked = app.entities.iter().any(|(other_index, other)| {
+                    other_index != index
+                        && other_index != target_index
+                        && other
+                            .raycast(entity.position, direction, distance - target.radius)
+                            .is_some()
+                });
+
+                !blocked
+            }
+            ComputerFiringKind::WithinCone { half_angle } => {
+                let direction = UnitComplex::new(displacement.y.atan2(displacement.x));
+                aim.angle_to(&direction).abs() <= *half_angle
+            }
+        };
 
-            let start_angle = aim_angle - projectile_sweep / 2.0;
+        if let Some(beam) = &mut self.beam {
+            let in_range = distance - target.radius < muzzle_length + beam.range;
+            beam.update(
+                (in_range && allowed).then_some(aim),
+                index,
+                entity,
+                delta_seconds,
+                app,
+            );
 
-            for i in 0..self.weapon.projectiles_per_shot {
-                let angle = start_angle + i as f32 * self.weapon.projectile_angle;
+            return;
+        }
 
-                let nudged_aim = UnitComplex::new(
-                    angle + rand::gen_range(-1.0, 1.0) * self.weapon.projectile_spread,
-                );
+        if self.cooldown <= 0.0 && allowed {
+            self.cooldown = match &mut self.firing_kind {
+                ComputerFiringKind::Burst {
+                    shots,
+                    interval,
+                    remaining,
+                } => {
+                    *remaining = remaining.checked_sub(1).unwrap_or(shots.saturating_sub(1));
+                    if *remaining > 0 {
+                        *interval
+                    } else {
+                        self.weapon.cooldown
+                    }
+                }
+                _ => self.weapon.cooldown,
+            };
 
-                app.insert_projectile(
-                    self.weapon.initial_speed,
-                    self.weapon.speed_exponent,
-                    nudged_aim,
-                    entity.position,
-                    muzzle_length,
-                    entity.color,
-                    entity.team,
-                    index,
-                );
-            }
+            self.weapon
+                .fire(aim, index, entity, Some(target_index), app);
         }
     }
 
-    pub fn aim(&self) -> Option<(UnitComplex<f32>, f32, SightKind)> {
-        if let Some(aim) = self.aim {
-            Some((
+    pub fn aim(&self) -> Option<(UnitComplex<f32>, f32, SightKind, f32)> {
+        self.aim.map(|aim| {
+            (
                 aim,
-                self.cooldown / self.weapon.cooldown,
+                match &self.beam {
+                    Some(beam) => beam.heat,
+                    None => self.cooldown / self.weapon.cooldown,
+                },
                 self.weapon.sight_kind,
-            ))
-        } else {
-            None
-        }
+                self.weapon.sight_size,
+            )
+        })
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub enum ComputerAimKind {
+    /// Points at the target, leading it by `aiming_lead`
+    #[default]
     PointTowards,
+    /// Always leads the target fully, ignoring `aiming_lead`
+    Predictive,
+    /// Swings back and forth across the target, spraying shots over an arc
+    Sweep {
+        /// Radians to either side of the target
+        amplitude: f32,
+        speed: f32,
+        phase: f32,
+    },
+    /// Ignores the target and always points in one direction
+    Fixed { angle: f32 },
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub enum ComputerFiringKind {
+    #[default]
     Always,
+    /// Only fires while the distance between the edges of the two entities is within range
     WithinDistance { distance: Range<f32> },
+    /// Holds fire while any other entity is in the way, so allies don't get shot
+    ClearLine,
+    /// Fires `shots` shots `interval` seconds apart, then waits out the weapon cooldown
+    Burst {
+        shots: usize,
+        interval: f32,
+        /// Shots left in the current burst
+        remaining: usize,
+    },
+    /// Only fires while the target is within `half_angle` of the aim, for fixed or sweeping aim
+    WithinCone { half_angle: f32 },
 }
 
 #[derive(Clone, Debug)]
@@ -200,6 +299,116 @@ pub struct Weapon {
     pub projectile_angle: f32,
     pub projectile_spread: f32,
     pub sight_kind: SightKind,
+    pub sight_size: f32,
+    /// Projectiles home in on the current target if this is set
+    pub guidance: Option<Guidance>,
+    pub interception: Interception,
+    pub damage: Damage,
+    pub projectile_size: Vector2<f32>,
+    pub lifetime: f32,
+    /// The color of the shooter is used if this is `None`
+    pub color: Option<Color>,
+    pub fade_in: f32,
+    pub fade_out: f32,
+    /// Impulse applied to the entity that is hit
+    pub knockback: f32,
+    /// Knockback is applied to everything within this radius of the impact if it is above 0.0
+    pub blast_radius: f32,
+    /// Applied to whatever armor the projectiles hit
+    pub status: Option<StatusEffect>,
+}
+
+impl Weapon {
+    /// Fires a single shot of `projectiles_per_shot` projectiles centered around `aim`.
+    pub fn fire(
+        &self,
+        aim: UnitComplex<f32>,
+        index: Index,
+        entity: &Entity,
+        target: Option<Index>,
+        app: &mut App,
+    ) {
+        self.fire_from(
+            aim,
+            entity.position,
+            entity.radius + 4.0,
+            index,
+            entity,
+            target,
+            app,
+        );
+    }
+
+    /// Like `fire`, but the projectiles start `offset_radius` away from `origin` instead of just
+    /// outside the entity.
+    #[allow(clippy::too_many_arguments)]
+    pub fn fire_from(
+        &self,
+        aim: UnitComplex<f32>,
+        origin: Point2<f32>,
+        offset_radius: f32,
+        index: Index,
+        entity: &Entity,
+        target: Option<Index>,
+        app: &mut App,
+    ) {
+        let projectile_sweep = (self.projectiles_per_shot - 1) as f32 * self.projectile_angle;
+
+        let start_angle = aim.angle() - projectile_sweep / 2.0;
+
+        Emitter::MUZZLE_FLASH.emit(
+            origin + util::displacement_from_angle(aim, offset_radius),
+            aim,
+            entity.velocity,
+            self.color.unwrap_or(entity.color),
+            &mut app.particles,
+        );
+
+        for i in 0..self.projectiles_per_shot {
+            let angle = start_angle + i as f32 * self.projectile_angle;
+
+            let nudged_aim =
+                UnitComplex::new(angle + rand::gen_range(-1.0, 1.0) * self.projectile_spread);
+
+            app.insert_projectile(
+                self,
+                nudged_aim,
+                origin,
+                offset_radius,
+                entity.color,
+                entity.team,
+                index,
+                self.guidance
+                    .map(|guidance| Guidance { target, ..guidance }),
+            );
+        }
+    }
+}
+
+impl Default for Weapon {
+    fn default() -> Self {
+        Self {
+            initial_speed: 48.0,
+            speed_exponent: 50.0,
+            cooldown: 1.0,
+            projectiles_per_shot: 1,
+            projectile_angle: 0.0,
+            projectile_spread: 0.0,
+            sight_kind: SightKind::Arrow,
+            sight_size: 1.0,
+            guidance: None,
+            interception: Default::default(),
+            damage: Damage::from_amount(1, DamageKind::Kinetic),
+            projectile_size: vector![1.0, 4.0],
+            lifetime: 1.0,
+            color: None,
+            fade_in: 0.1,
+            fade_out: 0.1,
+            knockback: 4.0,
+            blast_radius: 0.0,
+            status: None,
+        }
+    }
 }
 
 pub fn closest_target<'a>(
@@ -2,19 +2,14 @@ use crate::{
     app::App,
     computer_controller::{ComputerMotionController, ComputerShootingController},
     entity::Entity,
+    faction::Relationship,
     player_controller::{PlayerMotionController, PlayerShootingController},
+    status::JamTarget,
     util,
 };
 use nalgebra::UnitComplex;
 use thunderdome::Index;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum Team {
-    Player,
-    Neutral,
-    Hostile,
-}
-
 #[derive(Clone, Debug)]
 pub struct EntityController {
     pub targets: Vec<Index>,
@@ -24,6 +19,8 @@ pub struct EntityController {
 
 impl EntityController {
     pub const AGGRO_DISTANCE: f32 = 100.0;
+    /// Allies within this distance of an attacked entity join the fight
+    pub const ALERT_DISTANCE: f32 = 64.0;
 
     pub fn update(entity: &mut Entity, index: Index, delta_seconds: f32, app: &mut App) {
         let entity_unsafe_borrow = unsafe { &mut *(entity as *mut Entity) }; // causes UB if safety rules are broken
@@ -36,15 +33,13 @@ impl EntityController {
 
         let entity = entity_unsafe_borrow;
 
-        if entity.team == Team::Hostile {
-            for (other_index, other_entity) in &app.entities {
-                if index != other_index
-                    && other_entity.team == Team::Player
-                    && util::length_squared(entity.position - other_entity.position)
-                        < Self::AGGRO_DISTANCE.powi(2)
-                {
-                    controller.alert(other_index);
-                }
+        for (other_index, other_entity) in &app.entities {
+            if index != other_index
+                && app.factions.get(entity.team, other_entity.team) == Relationship::Hostile
+                && util::length_squared(entity.position - other_entity.position)
+                    < Self::AGGRO_DISTANCE.powi(2)
+            {
+                controller.alert(other_index);
             }
         }
 
@@ -62,12 +57,19 @@ impl EntityController {
         if let Some(motion) = controller.motion.as_mut() {
             match motion {
                 MotionController::Player(controller) => {
-                    controller.update(entity);
+                    controller.update(entity, delta_seconds);
+                }
+                MotionController::Computer(controller) => {
+                    controller.update(entity, targets, delta_seconds, app)
                 }
-                MotionController::Computer(controller) => controller.update(entity, targets, app),
             }
         }
 
+        // Jammed weapons keep their cooldowns frozen until the jam wears off
+        if entity.status.is_jammed(JamTarget::Weapons) {
+            return;
+        }
+
         if let Some(shooting) = controller.shooting.as_mut() {
             match shooting {
                 ShootingController::Player(control) => {
@@ -78,13 +80,20 @@ impl EntityController {
                 }
             }
         }
+
+        entity.update_mounts(index, targets, delta_seconds, app);
+    }
+
+    pub fn is_player(&self) -> bool {
+        matches!(self.motion, Some(MotionController::Player(_)))
+            || matches!(self.shooting, Some(ShootingController::Player(_)))
     }
 
     pub fn alert(&mut self, sender: Index) {
-        if self.targets.is_empty() {
-            if let Some(ShootingController::Computer(controller)) = &mut self.shooting {
-                controller.cooldown = controller.weapon.cooldown;
-            }
+        if self.targets.is_empty()
+            && let Some(ShootingController::Computer(controller)) = &mut self.shooting
+        {
+            controller.cooldown = controller.weapon.cooldown;
         }
 
         if !self.targets.contains(&sender) {
@@ -93,6 +102,8 @@ impl EntityController {
     }
 }
 
+// There is only ever one player, so boxing it wouldn't save anything
+#[allow(clippy::large_enum_variant)]
 #[derive(Clone, Debug)]
 pub enum MotionController {
     Player(PlayerMotionController),
@@ -106,12 +117,13 @@ pub enum ShootingController {
 }
 
 impl ShootingController {
-    pub fn aim(&self) -> Option<(UnitComplex<f32>, f32, SightKind)> {
+    pub fn aim(&self) -> Option<(UnitComplex<f32>, f32, SightKind, f32)> {
         match self {
             Self::Player(controller) => Some((
                 controller.aim,
                 controller.cooldown / controller.max_cooldown(),
                 SightKind::Arrow,
+                1.0,
             )),
             Self::Computer(controller) => controller.aim(),
         }
@@ -0,0 +1,61 @@
+#[derive(Clone, Copy, Debug)]
+pub struct Damage {
+    pub amount: u16,
+    pub kind: DamageKind,
+}
+
+impl Damage {
+    pub fn from_amount(amount: u16, kind: DamageKind) -> Self {
+        Self { amount, kind }
+    }
+
+    pub fn scaled(self, multiplier: f32) -> Self {
+        Self {
+            amount: (self.amount as f32 * multiplier).round() as u16,
+            ..self
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DamageKind {
+    Kinetic,
+    Energy,
+    Explosive,
+}
+
+/// Multipliers applied to incoming damage of each kind. 1.0 takes full damage, 0.0 is immune.
+#[derive(Clone, Copy, Debug)]
+pub struct Resistances {
+    pub kinetic: f32,
+    pub energy: f32,
+    pub explosive: f32,
+}
+
+impl Resistances {
+    pub const NONE: Self = Self {
+        kinetic: 1.0,
+        energy: 1.0,
+        explosive: 1.0,
+    };
+
+    pub fn multiplier(&self, kind: DamageKind) -> f32 {
+        match kind {
+            DamageKind::Kinetic => self.kinetic,
+            DamageKind::Energy => self.energy,
+            DamageKind::Explosive => self.explosive,
+        }
+    }
+
+    pub fn apply(&self, damage: Damage) -> u16 {
+        (damage.amount as f32 * self.multiplier(damage.kind))
+            .round()
+            .clamp(0.0, u16::MAX as f32) as u16
+    }
+}
+
+impl Default for Resistances {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
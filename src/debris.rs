@@ -0,0 +1,104 @@
+use macroquad::prelude::*;
+use nalgebra::{Point2, UnitComplex, Vector2, vector};
+
+use crate::{collision::Rectangle, util};
+
+/// A destroyed armor piece drifting away from where it broke off.
+#[derive(Clone, Copy, Debug)]
+pub struct Debris {
+    pub position: Point2<f32>,
+    pub velocity: Vector2<f32>,
+    pub angle: f32,
+    pub spin: f32,
+    /// Length along `angle`, then width
+    pub size: Vector2<f32>,
+    pub color: Color,
+    pub age: f32,
+    pub lifetime: f32,
+    /// Damage it can soak up as cover before breaking apart
+    pub health: u16,
+}
+
+impl Debris {
+    pub const LIFETIME: f32 = 2.0;
+    pub const FADE_OUT: f32 = 0.5;
+    /// Speed added away from the entity the piece broke off from
+    pub const EJECTION_SPEED: f32 = 8.0;
+    pub const DRAG: f32 = 0.5;
+
+    pub fn from_piece(
+        position: Point2<f32>,
+        size: Vector2<f32>,
+        angle: f32,
+        velocity: Vector2<f32>,
+        spin: f32,
+        color: Color,
+        health: u16,
+    ) -> Self {
+        Self {
+            position,
+            velocity,
+            angle,
+            spin,
+            size,
+            color,
+            age: 0.0,
+            lifetime: Self::LIFETIME,
+            health,
+        }
+    }
+
+    pub fn update(&mut self, gravity: Vector2<f32>, delta_seconds: f32) {
+        util::drift(
+            &mut self.position,
+            &mut self.velocity,
+            gravity,
+            Self::DRAG,
+            delta_seconds,
+        );
+        self.angle += self.spin * delta_seconds;
+        self.age += delta_seconds;
+    }
+
+    /// Breaks the debris apart once it has taken `amount` damage in total.
+    pub fn damage(&mut self, amount: u16) {
+        self.health = self.health.saturating_sub(amount);
+        if self.health == 0 {
+            self.age = self.lifetime;
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.age >= self.lifetime
+    }
+
+    pub fn radius_squared(&self) -> f32 {
+        util::length_squared(self.size) / 4.0
+    }
+
+    pub fn get_collider(&self) -> Rectangle {
+        Rectangle::from_dimensions(
+            self.position,
+            self.size,
+            vector![0.5, 0.5],
+            UnitComplex::new(self.angle),
+        )
+    }
+
+    pub fn draw(&self) {
+        draw_rectangle_ex(
+            self.position.x,
+            self.position.y,
+            self.size.x,
+            self.size.y,
+            DrawRectangleParams {
+                offset: vec2(0.5, 0.5),
+                rotation: self.angle,
+                color: Color {
+                    a: ((self.lifetime - self.age) / Self::FADE_OUT).min(1.0) * 0.75,
+                    ..self.color
+                },
+            },
+        );
+    }
+}
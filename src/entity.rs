@@ -1,27 +1,61 @@
-use std::f32::consts::TAU;
+use std::{f32::consts::TAU, ops::Range};
 
 use crate::{
     app::App,
-    components::{ArmorRing, Center},
-    controller::{EntityController, SightKind, Team},
+    boss::{Boss, Phase},
+    collision::Rectangle,
+    components::{Armor, ArmorRing, ArmorSlot, Center, Shield},
+    controller::{EntityController, ShootingController, SightKind},
+    damage::{Damage, DamageKind},
+    debris::Debris,
+    faction::Team,
+    hangar::{CarrierLink, Hangar},
+    pickup::DropTable,
+    satellite::Satellite,
+    status::{JamTarget, StatusEffects},
+    util,
 };
-use macroquad::prelude::*;
-use nalgebra::{Point2, Vector2};
+use macroquad::{prelude::*, rand};
+use nalgebra::{Point2, UnitComplex, Vector2, distance, vector};
 use thunderdome::Index;
 
 #[derive(Clone, Debug)]
 pub struct Entity {
     pub rings: Vec<ArmorRing>,
     pub center: Center,
+    pub shield: Option<Shield>,
     pub position: Point2<f32>,
     pub velocity: Vector2<f32>,
+    /// Acceleration requested by the motion controller, reset every update
+    pub thrust: Vector2<f32>,
+    pub acceleration: f32,
+    pub max_speed: f32,
+    /// Fraction of velocity lost per second, 0.0 lets the entity coast in orbit forever
+    pub drag: f32,
+    pub mass: f32,
+    pub gravity: f32,
     pub radius: f32,
+    pub colliders: ColliderCache,
     pub color: Color,
     pub controller: Option<EntityController>,
     pub team: Team,
+    pub drops: DropTable,
+    /// Set while the entity is orbiting a parent instead of moving on its own
+    pub satellite: Option<Satellite>,
+    pub status: StatusEffects,
+    pub hangar: Option<Hangar>,
+    /// Set on drones launched from a carrier's hangar
+    pub carrier: Option<CarrierLink>,
+    pub boss: Option<Boss>,
 }
 
 impl Entity {
+    pub const DEFAULT_ACCELERATION: f32 = 144.0;
+    pub const DEFAULT_MAX_SPEED: f32 = 96.0;
+    pub const DEFAULT_DRAG: f32 = 0.5;
+    /// Mass per unit of armor area per point of maximum health
+    pub const ARMOR_DENSITY: f32 = 1.0 / 64.0;
+
     pub fn from_rings(
         position: Point2<f32>,
         color: Color,
@@ -32,16 +66,51 @@ impl Entity {
     ) -> Self {
         let radius = Self::get_radius_squared(&rings, &center).sqrt();
         let velocity = Default::default();
-        Self {
+        let thrust = Default::default();
+        let mass = Self::get_mass(&rings, &center);
+        let colliders = Default::default();
+        let mut entity = Self {
             rings,
             center,
+            shield: None,
             position,
             velocity,
+            thrust,
+            acceleration: Self::DEFAULT_ACCELERATION,
+            max_speed: Self::DEFAULT_MAX_SPEED,
+            drag: Self::DEFAULT_DRAG,
+            mass,
+            gravity: 0.0,
             radius,
+            colliders,
             color,
             controller,
             team,
-        }
+            drops: Default::default(),
+            satellite: None,
+            status: Default::default(),
+            hangar: None,
+            carrier: None,
+            boss: None,
+        };
+        entity.update_colliders();
+        entity
+    }
+
+    pub fn with_drops(mut self, drops: DropTable) -> Self {
+        self.drops = drops;
+        self
+    }
+
+    pub fn with_hangar(mut self, hangar: Hangar) -> Self {
+        self.hangar = Some(hangar);
+        self
+    }
+
+    /// Makes this a boss, entering the next of `phases` whenever one of its rings is destroyed.
+    pub fn with_boss(mut self, name: &'static str, phases: Vec<Phase>) -> Self {
+        self.boss = Some(Boss::from_phases(name, &self, phases));
+        self
     }
 
     pub fn draw(&self) {
@@ -49,8 +118,28 @@ impl Entity {
         for ring in &*self.rings {
             ring.draw_around(self.position, self.color);
         }
+        if let Some(shield) = &self.shield {
+            shield.draw_around(self.position, self.get_shield_radius(), self.color);
+        }
+        self.status
+            .draw_around(self.position, self.get_shield_radius());
 
         self.draw_sight();
+        self.draw_beam();
+    }
+
+    pub fn draw_beam(&self) -> Option<()> {
+        // The beam didn't update, so its trace is left over from before
+        if self.status.is_stunned() || self.status.is_jammed(JamTarget::Weapons) {
+            return None;
+        }
+        let beam = match self.controller.as_ref()?.shooting.as_ref()? {
+            ShootingController::Player(controller) => controller.beam.as_ref()?,
+            ShootingController::Computer(controller) => controller.beam.as_ref()?,
+        };
+        beam.draw(self.color);
+
+        Some(())
     }
 
     pub fn draw_sight(&self) -> Option<()> {
@@ -115,47 +204,424 @@ impl Entity {
         Some(())
     }
 
-    pub fn update(&mut self, index: Index, delta_seconds: f32, app: &mut App) {
+    /// Returning `None` indicates a request for deletion, after burning to death.
+    pub fn update(&mut self, index: Index, delta_seconds: f32, app: &mut App) -> Option<()> {
+        // Destroyed by a beam earlier in this timestep and waiting to be removed
+        self.center.armor?;
+
+        if let Some(burn) = self.status.update(delta_seconds) {
+            self.burn(burn, &mut app.debris);
+            self.check_deletion()?;
+        }
+
+        // Taken out so the phase can change the rest of the entity
+        if let Some(mut boss) = self.boss.take() {
+            boss.update(self, app);
+            self.boss = Some(boss);
+        }
+
         self.center.update(delta_seconds);
+        self.center.regenerate(delta_seconds);
+        if let Some(shield) = &mut self.shield {
+            shield.update(delta_seconds);
+        }
+
+        let jammed = self.status.is_jammed(JamTarget::Rings);
+        let mut regrew = false;
         for ring in &mut *self.rings {
+            if jammed {
+                ring.speed = 0.0;
+            } else {
+                ring.rotate(delta_seconds);
+            }
             ring.update(delta_seconds);
+            regrew |= ring.regenerate(delta_seconds);
+        }
+        if regrew {
+            self.update_shape();
         }
 
-        EntityController::update(self, index, delta_seconds, app);
+        let mut recalled_to = None;
+        if let Some(link) = self.carrier {
+            match app.entities.get(link.carrier) {
+                Some(carrier) => {
+                    if carrier
+                        .hangar
+                        .as_ref()
+                        .is_some_and(|hangar| hangar.recalling)
+                    {
+                        recalled_to = Some((carrier.position, carrier.velocity));
+                    }
+                }
+                None if link.self_destruct => return None,
+                None => self.carrier = None,
+            }
+        }
+
+        if !self.status.is_stunned() {
+            match recalled_to {
+                // Flies back to dock, the hangar takes it in once it touches the carrier
+                Some((position, velocity)) => {
+                    // Sitting right on top of the carrier, so just keep pace until docked
+                    let approach = (position - self.position)
+                        .try_normalize(f32::EPSILON)
+                        .map(|direction| direction * self.max_speed)
+                        .unwrap_or_default();
+                    self.thrust_towards(velocity + approach, delta_seconds);
+                }
+                None => EntityController::update(self, index, delta_seconds, app),
+            }
+        }
+        self.update_hangar(index, delta_seconds, app);
+
+        if let Some(satellite) = &mut self.satellite {
+            match satellite.update(delta_seconds, app) {
+                Some((position, velocity)) => {
+                    self.position = position;
+                    self.velocity = velocity;
+                    self.thrust = Default::default();
+                    self.update_colliders();
+                    return Some(());
+                }
+                // The parent died, so fly off with the velocity the orbit left behind
+                None => self.satellite = None,
+            }
+        }
+
+        let slow = self.status.speed_multiplier();
+        let max_speed = self.max_speed * slow;
+        let gravity = app.gravity.acceleration_at(self.position, Some(index));
+        self.velocity += (self.thrust * slow + gravity) * delta_seconds;
+        self.velocity *= (1.0 - self.drag).powf(delta_seconds);
+        if util::length_squared(self.velocity) > max_speed.powi(2) {
+            self.velocity = self.velocity.normalize() * max_speed;
+        }
+        self.thrust = Default::default();
 
         self.position += self.velocity * delta_seconds;
+
+        self.update_colliders();
+
+        Some(())
+    }
+
+    /// Damages a random remaining armor piece, preferring the rings so the center burns last.
+    pub fn burn(&mut self, damage: Damage, debris: &mut Vec<Debris>) {
+        let pieces: Vec<_> = self
+            .rings
+            .iter()
+            .enumerate()
+            .flat_map(|(ring_index, ring)| {
+                ring.armor
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, armor)| armor.is_some())
+                    .map(move |(slot, _)| ArmorSlot::Ring {
+                        ring: ring_index,
+                        slot,
+                    })
+            })
+            .collect();
+
+        let slot = if pieces.is_empty() {
+            ArmorSlot::Center
+        } else {
+            pieces[rand::gen_range(0, pieces.len())]
+        };
+        self.damage(slot, damage, debris);
+    }
+
+    /// Rebuilds the collider cache. This must be called whenever armor moves or is damaged.
+    pub fn update_colliders(&mut self) {
+        let colliders = &mut self.colliders;
+        colliders.colliders.clear();
+        colliders.bands.clear();
+
+        for (i, ring) in self.rings.iter().enumerate() {
+            let start = colliders.colliders.len();
+            ring.push_colliders(i, self.position, &mut colliders.colliders);
+            colliders
+                .bands
+                .push((ring.get_band(), start..colliders.colliders.len()));
+        }
+
+        if self.center.armor.is_some() {
+            let start = colliders.colliders.len();
+            colliders
+                .colliders
+                .push((self.center.get_collider(self.position), ArmorSlot::Center));
+            colliders.bands.push((
+                0.0..self.center.get_radius_squared().sqrt(),
+                start..colliders.colliders.len(),
+            ));
+        }
+    }
+
+    /// Damages the armor in `slot`, interrupting the regeneration of its ring or center. If the
+    /// piece is destroyed, it breaks off into `debris`.
+    pub fn damage(&mut self, slot: ArmorSlot, damage: Damage, debris: &mut Vec<Debris>) {
+        let regeneration = match slot {
+            ArmorSlot::Center => &mut self.center.regeneration,
+            ArmorSlot::Ring { ring, .. } => &mut self.rings[ring].regeneration,
+        };
+        if let Some(regeneration) = regeneration {
+            regeneration.interrupt();
+        }
+
+        let piece = self.get_debris(slot);
+        Armor::damage(self.armor_mut(slot), damage);
+        if self.armor_mut(slot).is_none() {
+            debris.extend(piece);
+
+            if let ArmorSlot::Ring { ring, slot } = slot {
+                self.rings[ring].mounts.retain(|mount| mount.slot != slot);
+            }
+        }
+    }
+
+    pub fn update_mounts(
+        &mut self,
+        index: Index,
+        targets: &[Index],
+        delta_seconds: f32,
+        app: &mut App,
+    ) {
+        for ring in 0..self.rings.len() {
+            // Taken out so the mounts can see the rest of the entity while firing
+            let mut mounts = std::mem::take(&mut self.rings[ring].mounts);
+            for mount in &mut mounts {
+                mount.update(ring, index, self, targets, delta_seconds, app);
+            }
+            self.rings[ring].mounts = mounts;
+        }
+    }
+
+    /// Launches and docks drones if the entity is a carrier. This runs outside the controller, so
+    /// the hangar keeps recalling and docking while the carrier is stunned or jammed.
+    pub fn update_hangar(&mut self, index: Index, delta_seconds: f32, app: &mut App) {
+        // Taken out so the hangar can see the rest of the entity while launching
+        let Some(mut hangar) = self.hangar.take() else {
+            return;
+        };
+
+        let targets: Vec<_> = self
+            .controller
+            .iter()
+            .flat_map(|controller| &controller.targets)
+            .copied()
+            .filter(|&target| app.entities.contains(target))
+            .collect();
+        let launching = !self.status.is_stunned() && !self.status.is_jammed(JamTarget::Weapons);
+
+        hangar.update(index, self, &targets, launching, delta_seconds, app);
+        self.hangar = Some(hangar);
+    }
+
+    /// Returns the debris the armor piece in `slot` would leave behind if destroyed right now.
+    pub fn get_debris(&self, slot: ArmorSlot) -> Option<Debris> {
+        match slot {
+            ArmorSlot::Center => {
+                let armor = self.center.armor?;
+                Some(Debris::from_piece(
+                    self.position,
+                    armor.size,
+                    self.center.angle,
+                    self.velocity,
+                    self.center.speed,
+                    WHITE,
+                    armor.max_health.get(),
+                ))
+            }
+            ArmorSlot::Ring { ring, slot } => {
+                let ring = &self.rings[ring];
+                let armor = ring.armor[slot]?;
+                let (base, direction) = ring.get_slot_transform(slot);
+                let middle = base + direction * vector![armor.size.y / 2.0, 0.0];
+
+                // Flung outwards, keeping the speed it had from the ring's rotation
+                let spin_velocity = vector![-middle.y, middle.x] * ring.speed;
+                let velocity = self.velocity
+                    + direction * vector![Debris::EJECTION_SPEED, 0.0]
+                    + spin_velocity;
+
+                Some(Debris::from_piece(
+                    self.position + middle,
+                    vector![armor.size.y, armor.size.x],
+                    direction.angle(),
+                    velocity,
+                    ring.speed,
+                    self.color,
+                    armor.max_health.get(),
+                ))
+            }
+        }
+    }
+
+    /// Breaks every remaining ring piece off into `debris`, for when the entity dies.
+    pub fn shed_debris(&self, debris: &mut Vec<Debris>) {
+        for (ring_index, ring) in self.rings.iter().enumerate() {
+            debris.extend((0..ring.armor.len()).filter_map(|slot| {
+                self.get_debris(ArmorSlot::Ring {
+                    ring: ring_index,
+                    slot,
+                })
+            }));
+        }
+    }
+
+    pub fn armor_mut(&mut self, slot: ArmorSlot) -> &mut Option<Armor> {
+        match slot {
+            ArmorSlot::Center => &mut self.center.armor,
+            ArmorSlot::Ring { ring, slot } => &mut self.rings[ring].armor[slot],
+        }
+    }
+
+    /// Thrusts as hard as possible towards reaching `velocity` during this update.
+    pub fn thrust_towards(&mut self, velocity: Vector2<f32>, delta_seconds: f32) {
+        let drag_compensated = self.velocity * (1.0 - self.drag).powf(delta_seconds);
+        let thrust = (velocity - drag_compensated) / delta_seconds;
+
+        self.thrust = if util::length_squared(thrust) > self.acceleration.powi(2) {
+            thrust.normalize() * self.acceleration
+        } else {
+            thrust
+        };
+    }
+
+    /// Lets go of the parent, flying off with the current orbital velocity.
+    pub fn detach(&mut self) {
+        self.satellite = None;
+    }
+
+    pub fn apply_impulse(&mut self, impulse: Vector2<f32>) {
+        self.velocity += impulse / self.mass;
     }
 
     /// Returning `None` indicates a request for deletion.
     pub fn check_deletion(&mut self) -> Option<()> {
-        if self.center.armor.is_none() {
-            return None;
-        }
+        self.center.armor?;
+        self.update_shape();
 
+        Some(())
+    }
+
+    /// Drops rings that are gone for good and recomputes everything derived from the armor. This
+    /// must be called whenever an armor piece is destroyed or regrown.
+    pub fn update_shape(&mut self) {
         let mut radius_squared = self.center.get_radius_squared();
         for i in (0..self.rings.len()).rev() {
             let ring = &mut self.rings[i];
 
             if let Some(ring_radius) = ring.get_full_radius_squared() {
                 radius_squared = radius_squared.max(ring_radius);
-            } else {
+            } else if ring.regeneration.is_none() {
                 self.rings.swap_remove(i);
             }
         }
         self.radius = radius_squared.sqrt();
+        self.mass = Self::get_mass(&self.rings, &self.center);
+        self.update_colliders();
+    }
 
-        Some(())
+    /// Returns the distance along the ray to the closest armor piece within `max_distance`, along
+    /// with the slot of that armor piece.
+    pub fn raycast(
+        &self,
+        origin: Point2<f32>,
+        direction: UnitComplex<f32>,
+        max_distance: f32,
+    ) -> Option<(f32, ArmorSlot)> {
+        let direction = vector![direction.re, direction.im];
+
+        let along = (self.position - origin)
+            .dot(&direction)
+            .clamp(0.0, max_distance);
+        let closest_approach = distance(&(origin + direction * along), &self.position);
+        if closest_approach > self.radius {
+            return None;
+        }
+
+        self.colliders
+            .query(closest_approach..self.radius)
+            .filter_map(|(rect, slot)| Some((rect.ray_distance(origin, direction)?, *slot)))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+    }
+
+    /// Returns the distance along the ray to where it enters the shield, if the shield is up and
+    /// blocks damage of this kind.
+    pub fn shield_raycast(
+        &self,
+        origin: Point2<f32>,
+        direction: UnitComplex<f32>,
+        max_distance: f32,
+        kind: DamageKind,
+    ) -> Option<f32> {
+        if !self.shield.as_ref()?.blocks(kind) {
+            return None;
+        }
+
+        let direction = vector![direction.re, direction.im];
+        let radius = self.get_shield_radius();
+
+        let along = (self.position - origin).dot(&direction);
+        let closest_approach_squared = util::length_squared(self.position - origin) - along.powi(2);
+        let half_chord_squared = radius.powi(2) - closest_approach_squared;
+        if half_chord_squared < 0.0 {
+            return None;
+        }
+
+        // Rays starting inside the shield don't hit it
+        let distance = along - half_chord_squared.sqrt();
+        (0.0..=max_distance).contains(&distance).then_some(distance)
+    }
+
+    /// The shield sits just outside the outermost ring, so it shrinks as rings are destroyed.
+    pub fn get_shield_radius(&self) -> f32 {
+        self.radius + self.shield.as_ref().map_or(0.0, |shield| shield.margin)
     }
 
     pub fn get_full_radius(&self) -> f32 {
         Self::get_radius_squared(&self.rings, &self.center).sqrt()
     }
 
-    fn get_radius_squared(rings: &Vec<ArmorRing>, center: &Center) -> f32 {
+    fn get_mass(rings: &[ArmorRing], center: &Center) -> f32 {
+        let armor_mass = |armor: &Armor| {
+            armor.size.x * armor.size.y * armor.max_health.get() as f32 * Self::ARMOR_DENSITY
+        };
+
+        rings
+            .iter()
+            .flat_map(|ring| ring.armor.iter().flatten())
+            .chain(center.armor.as_ref())
+            .map(armor_mass)
+            .sum()
+    }
+
+    fn get_radius_squared(rings: &[ArmorRing], center: &Center) -> f32 {
         rings
-            .into_iter()
+            .iter()
             .map(|r| r.get_full_radius_squared().unwrap_or(0.0))
             .max_by(|x, y| x.partial_cmp(y).unwrap())
             .unwrap_or_else(|| center.get_radius_squared())
     }
 }
+
+/// Colliders of every armor piece, rebuilt once per timestep after the rings have rotated.
+#[derive(Clone, Debug, Default)]
+pub struct ColliderCache {
+    pub colliders: Vec<(Rectangle, ArmorSlot)>,
+    /// The distances from the entity position covered by each ring (and the center), along with
+    /// the range of `colliders` belonging to it
+    pub bands: Vec<(Range<f32>, Range<usize>)>,
+}
+
+impl ColliderCache {
+    /// Returns the colliders of every band overlapping `distance`.
+    pub fn query(&self, distance: Range<f32>) -> impl Iterator<Item = &(Rectangle, ArmorSlot)> {
+        self.bands
+            .iter()
+            .filter(move |(band, _)| band.start <= distance.end && distance.start <= band.end)
+            .flat_map(|(_, colliders)| &self.colliders[colliders.clone()])
+    }
+}
@@ -0,0 +1,85 @@
+/// Index of a faction in `Factions`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Team(pub usize);
+
+impl Team {
+    pub const PLAYER: Self = Self(0);
+    pub const NEUTRAL: Self = Self(1);
+    pub const HOSTILE: Self = Self(2);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Relationship {
+    /// Auto-aggros when close
+    Hostile,
+    /// Only fights back when attacked
+    Neutral,
+    /// Shots pass through, and attacks are answered together
+    Allied,
+}
+
+/// Symmetric matrix of the relationships between every pair of factions.
+#[derive(Clone, Debug)]
+pub struct Factions {
+    count: usize,
+    relationships: Vec<Relationship>,
+}
+
+impl Factions {
+    /// Adds a faction that is allied with itself and neutral towards everyone else.
+    pub fn add(&mut self) -> Team {
+        let count = self.count + 1;
+        let mut relationships = vec![Relationship::Neutral; count * count];
+
+        for a in 0..self.count {
+            for b in 0..self.count {
+                relationships[a * count + b] = self.relationships[a * self.count + b];
+            }
+        }
+        relationships[count * count - 1] = Relationship::Allied;
+
+        self.count = count;
+        self.relationships = relationships;
+
+        Team(count - 1)
+    }
+
+    pub fn get(&self, a: Team, b: Team) -> Relationship {
+        self.relationships[a.0 * self.count + b.0]
+    }
+
+    pub fn set(&mut self, a: Team, b: Team, relationship: Relationship) {
+        self.relationships[a.0 * self.count + b.0] = relationship;
+        self.relationships[b.0 * self.count + a.0] = relationship;
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+}
+
+impl Default for Factions {
+    /// The player, neutral and hostile factions, where only hostile and player are at war.
+    fn default() -> Self {
+        let mut factions = Self {
+            count: 0,
+            relationships: Vec::new(),
+        };
+
+        let player = factions.add();
+        let neutral = factions.add();
+        let hostile = factions.add();
+        debug_assert_eq!(
+            [player, neutral, hostile],
+            [Team::PLAYER, Team::NEUTRAL, Team::HOSTILE]
+        );
+
+        factions.set(hostile, player, Relationship::Hostile);
+
+        factions
+    }
+}
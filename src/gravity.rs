@@ -0,0 +1,129 @@
+use macroquad::prelude::*;
+use nalgebra::{Point2, Vector2};
+use thunderdome::{Arena, Index};
+
+use crate::{entity::Entity, util};
+
+/// A static body that pulls on entities and absorbs projectiles.
+#[derive(Clone, Debug)]
+pub struct Planet {
+    pub position: Point2<f32>,
+    pub radius: f32,
+    /// Acceleration at a distance of one unit from the center
+    pub strength: f32,
+    pub color: Color,
+}
+
+impl Planet {
+    pub fn from_strength(position: Point2<f32>, radius: f32, strength: f32, color: Color) -> Self {
+        Self {
+            position,
+            radius,
+            strength,
+            color,
+        }
+    }
+
+    /// Returns the speed needed to circle the planet at `distance` from its center.
+    pub fn orbital_speed(&self, distance: f32) -> f32 {
+        (self.strength / distance).sqrt()
+    }
+
+    pub fn draw(&self) {
+        draw_circle(
+            self.position.x,
+            self.position.y,
+            self.radius,
+            Color {
+                a: 0.25,
+                ..self.color
+            },
+        );
+        draw_circle_lines(
+            self.position.x,
+            self.position.y,
+            self.radius,
+            0.5,
+            self.color,
+        );
+    }
+}
+
+/// A point that pulls on everything around it.
+#[derive(Clone, Copy, Debug)]
+pub struct GravitySource {
+    pub position: Point2<f32>,
+    /// Acceleration at a distance of one unit from the center
+    pub strength: f32,
+    /// Inside this distance the pull weakens towards the center instead of growing without bound
+    pub radius: f32,
+    /// The entity creating this source, which isn't pulled by itself
+    pub entity: Option<Index>,
+}
+
+impl GravitySource {
+    pub fn acceleration_at(&self, position: Point2<f32>) -> Vector2<f32> {
+        let displacement = self.position - position;
+        let distance = util::length(displacement).max(self.radius);
+
+        displacement * self.strength / distance.powi(3)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Gravity {
+    pub planets: Vec<Planet>,
+    /// Every planet and massive entity, rebuilt at the start of each timestep
+    pub sources: Vec<GravitySource>,
+}
+
+impl Gravity {
+    pub fn update_sources(&mut self, entities: &Arena<Entity>) {
+        self.sources.clear();
+
+        self.sources
+            .extend(self.planets.iter().map(|planet| GravitySource {
+                position: planet.position,
+                strength: planet.strength,
+                radius: planet.radius,
+                entity: None,
+            }));
+
+        self.sources.extend(
+            entities
+                .iter()
+                .filter(|(_, entity)| entity.gravity > 0.0)
+                .map(|(index, entity)| GravitySource {
+                    position: entity.position,
+                    strength: entity.gravity,
+                    radius: entity.radius,
+                    entity: Some(index),
+                }),
+        );
+    }
+
+    /// Returns the total pull at `position`, ignoring the source created by `exclude`.
+    pub fn acceleration_at(&self, position: Point2<f32>, exclude: Option<Index>) -> Vector2<f32> {
+        self.sources
+            .iter()
+            .filter(|source| source.entity.is_none() || source.entity != exclude)
+            .map(|source| source.acceleration_at(position))
+            .sum()
+    }
+
+    pub fn is_inside_planet(&self, position: Point2<f32>) -> bool {
+        self.planets
+            .iter()
+            .any(|planet| util::length_squared(planet.position - position) < planet.radius.powi(2))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    pub fn draw(&self) {
+        for planet in &self.planets {
+            planet.draw();
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use nalgebra::{Point2, UnitComplex};
+use thunderdome::Index;
+
+use crate::{
+    app::App,
+    faction::{Relationship, Team},
+    util,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Guidance {
+    pub target: Option<Index>,
+    pub kind: GuidanceKind,
+    pub turn_rate: f32,
+    /// Half angle of the cone in front of the projectile in which new targets are acquired
+    pub lock_cone: f32,
+    /// Angle of the line of sight to the target during the previous update
+    pub line_of_sight: Option<f32>,
+}
+
+impl Guidance {
+    pub fn from_turn_rate(kind: GuidanceKind, turn_rate: f32, lock_cone: f32) -> Self {
+        Self {
+            target: None,
+            kind,
+            turn_rate,
+            lock_cone,
+            line_of_sight: None,
+        }
+    }
+
+    /// Turns `angle` towards the target, acquiring a new target if the current one is missing.
+    pub fn steer(
+        &mut self,
+        position: Point2<f32>,
+        angle: &mut UnitComplex<f32>,
+        team: Option<Team>,
+        delta_seconds: f32,
+        app: &App,
+    ) {
+        let target = match self.target.and_then(|index| app.entities.get(index)) {
+            Some(target) => target,
+            None => {
+                // The target died mid-flight, so look for a new one ahead and fly straight if
+                // there is nothing to lock onto.
+                self.line_of_sight = None;
+                self.target = self.acquire(position, *angle, team, app);
+
+                let Some(target) = self.target.map(|index| &app.entities[index]) else {
+                    return;
+                };
+                target
+            }
+        };
+
+        let displacement = target.position - position;
+        let line_of_sight = displacement.y.atan2(displacement.x);
+
+        let turn = match self.kind {
+            GuidanceKind::Pursuit => util::angle_difference(line_of_sight, angle.angle()),
+            GuidanceKind::ProportionalNavigation { gain } => match self.line_of_sight {
+                Some(previous) => gain * util::angle_difference(line_of_sight, previous),
+                None => 0.0,
+            },
+        };
+        self.line_of_sight = Some(line_of_sight);
+
+        let max_turn = self.turn_rate * delta_seconds;
+        *angle = UnitComplex::new(angle.angle() + turn.clamp(-max_turn, max_turn));
+    }
+
+    /// Finds the closest non-allied entity inside the lock cone.
+    pub fn acquire(
+        &self,
+        position: Point2<f32>,
+        angle: UnitComplex<f32>,
+        team: Option<Team>,
+        app: &App,
+    ) -> Option<Index> {
+        app.entities
+            .iter()
+            .filter(|(_, entity)| match team {
+                Some(team) => app.factions.get(team, entity.team) != Relationship::Allied,
+                None => true,
+            })
+            .filter_map(|(index, entity)| {
+                let displacement = entity.position - position;
+                let bearing = displacement.y.atan2(displacement.x);
+
+                (util::angle_difference(bearing, angle.angle()).abs() <= self.lock_cone)
+                    .then(|| (index, util::length_squared(displacement)))
+            })
+            .reduce(|a, b| if a.1 < b.1 { a } else { b })
+            .map(|(index, _)| index)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum GuidanceKind {
+    /// Always turns to point directly at the target.
+    Pursuit,
+    /// Turns proportionally to the rotation of the line of sight, leading moving targets.
+    ProportionalNavigation { gain: f32 },
+}
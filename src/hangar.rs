@@ -0,0 +1,114 @@
+use macroquad::rand;
+use nalgebra::{Point2, UnitComplex, vector};
+use thunderdome::Index;
+
+use crate::{app::App, entity::Entity, util};
+
+/// Launches drones built by an archetype function, up to `capacity` at a time. While the carrier
+/// has no targets, its drones are recalled and dock, freeing up their slots.
+#[derive(Clone, Debug)]
+pub struct Hangar {
+    /// Builds a drone at the given position. Team, targets and velocity are set on launch.
+    pub archetype: fn(Point2<f32>) -> Entity,
+    pub capacity: usize,
+    pub launch_interval: f32,
+    pub cooldown: f32,
+    pub launch_speed: f32,
+    /// Drones blow up when the carrier dies if this is set, otherwise they fight on alone
+    pub self_destruct: bool,
+    /// Set while the carrier has nothing to fight, so its drones fly back to dock
+    pub recalling: bool,
+}
+
+impl Hangar {
+    pub const DEFAULT_LAUNCH_SPEED: f32 = 24.0;
+
+    pub fn from_archetype(
+        archetype: fn(Point2<f32>) -> Entity,
+        capacity: usize,
+        launch_interval: f32,
+    ) -> Self {
+        Self {
+            archetype,
+            capacity,
+            launch_interval,
+            cooldown: launch_interval,
+            launch_speed: Self::DEFAULT_LAUNCH_SPEED,
+            self_destruct: false,
+            recalling: false,
+        }
+    }
+
+    pub fn with_self_destruct(mut self) -> Self {
+        self.self_destruct = true;
+        self
+    }
+
+    /// Docks drones that made it back while recalling, and launches a new drone at `targets` once
+    /// the cooldown is up and there is room. While `launching` is off, as when the carrier is
+    /// stunned or its weapons are jammed, the cooldown is frozen and nothing launches, but recalls
+    /// and docking carry on.
+    pub fn update(
+        &mut self,
+        index: Index,
+        entity: &Entity,
+        targets: &[Index],
+        launching: bool,
+        delta_seconds: f32,
+        app: &mut App,
+    ) {
+        self.recalling = targets.is_empty();
+        if launching {
+            self.cooldown = (self.cooldown - delta_seconds).max(0.0);
+        }
+
+        let mut drones = 0;
+        for (drone_index, drone) in &app.entities {
+            if drone.carrier.is_none_or(|link| link.carrier != index) {
+                continue;
+            }
+            drones += 1;
+
+            if self.recalling
+                && util::length_squared(drone.position - entity.position)
+                    < (entity.radius + drone.radius).powi(2)
+            {
+                app.despawns.push(drone_index);
+            }
+        }
+        drones += app
+            .spawns
+            .iter()
+            .filter(|drone| drone.carrier.is_some_and(|link| link.carrier == index))
+            .count();
+
+        if !launching || self.recalling || self.cooldown > 0.0 || drones >= self.capacity {
+            return;
+        }
+        self.cooldown = self.launch_interval;
+
+        let direction = UnitComplex::new(rand::gen_range(0.0, std::f32::consts::TAU));
+        let position = entity.position + direction * vector![entity.radius + 2.0, 0.0];
+
+        let mut drone = (self.archetype)(position);
+        drone.team = entity.team;
+        drone.color = entity.color;
+        drone.velocity = entity.velocity + direction * vector![self.launch_speed, 0.0];
+        drone.carrier = Some(CarrierLink {
+            carrier: index,
+            self_destruct: self.self_destruct,
+        });
+        if let Some(controller) = &mut drone.controller {
+            controller.targets = targets.to_vec();
+        }
+
+        app.spawns.push(drone);
+    }
+}
+
+/// Ties a launched drone to the carrier it came from.
+#[derive(Clone, Copy, Debug)]
+pub struct CarrierLink {
+    pub carrier: Index,
+    pub self_destruct: bool,
+}
@@ -15,13 +15,13 @@ impl InputAxis {
 
     pub fn update_state(&mut self) {
         Self::update(
-            (&self.positive.0).into_iter().any(|b| b.is_down()),
+            self.positive.0.iter().any(|b| b.is_down()),
             &mut self.positive.1,
             &mut self.negative.1,
         );
 
         Self::update(
-            (&self.negative.0).into_iter().any(|b| b.is_down()),
+            self.negative.0.iter().any(|b| b.is_down()),
             &mut self.negative.1,
             &mut self.positive.1,
         );
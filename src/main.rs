@@ -1,23 +1,44 @@
 use std::f32::consts::TAU;
 
+use beam::Beam;
 use computer_controller::Weapon;
 use controller::SightKind;
+use damage::{Damage, DamageKind, Resistances};
 use entity::Entity;
+use faction::{Relationship, Team};
+use guidance::{Guidance, GuidanceKind};
 use macroquad::prelude::*;
-use nalgebra::{Point2, point, vector};
+use nalgebra::{Point2, UnitComplex, point, vector};
+use pickup::{DropTable, PickupKind, WeaponBuff};
+use status::{JamTarget, StatusEffect, StatusKind};
+use thunderdome::Index;
 
 pub mod app;
 
+pub mod beam;
+pub mod boss;
+pub mod guidance;
+pub mod mount;
 pub mod projectile;
 
 pub mod camera;
 pub mod components;
+pub mod damage;
+pub mod debris;
 pub mod entity;
+pub mod faction;
+pub mod gravity;
+pub mod hangar;
 pub mod mouse_display;
+pub mod particle;
+pub mod pickup;
+pub mod satellite;
+pub mod status;
 
 pub mod computer_controller;
 pub mod controller;
 pub mod player_controller;
+pub mod rules;
 
 pub mod collision;
 pub mod input;
@@ -25,6 +46,13 @@ pub mod util;
 
 const START_IN_FULLSCREEN: bool = true;
 
+const REPAIR: PickupKind = PickupKind::Repair { amount: 2 };
+const OVERCHARGE: PickupKind = PickupKind::WeaponBuff(WeaponBuff {
+    cooldown_multiplier: 0.5,
+    damage_multiplier: 2.0,
+    duration: 8.0,
+});
+
 fn window_conf() -> Conf {
     Conf {
         window_title: "Orbit".to_owned(),
@@ -41,9 +69,11 @@ async fn main() {
     let player_index = app.entities.insert(entity::Entity::from_rings(
         point![-64.0, 0.0],
         Color::from_hex(0x0000ff),
-        components::Center::from_size(vector![2.0, 2.0], 16, -TAU / 6.0),
+        components::Center::from_size(vector![2.0, 2.0], 16, -TAU / 6.0)
+            .with_regeneration(components::Regeneration::from_rate(5.0, 1.0, 0.0)),
         vec![
-            components::ArmorRing::from_size(vector![4.0, 1.0], 8, 4, 3.5, TAU / 12.0),
+            components::ArmorRing::from_size(vector![4.0, 1.0], 8, 4, 3.5, TAU / 12.0)
+                .with_regeneration(components::Regeneration::from_rate(3.0, 2.0, 4.0)),
             // *A gift to rustfmt to keep it from messing this code up*
         ],
         Some(controller::EntityController {
@@ -51,9 +81,20 @@ async fn main() {
             motion: Some(controller::MotionController::Player(Default::default())),
             shooting: Some(controller::ShootingController::Player(Default::default())),
         }),
-        controller::Team::Player,
+        Team::PLAYER,
     ));
 
+    // A pair of drones circling the player, released with G
+    for angle in [0.0, TAU / 2.0] {
+        app.entities.insert(drone(
+            player_index,
+            &app.entities[player_index],
+            angle,
+            Color::from_hex(0x0080ff),
+            Team::PLAYER,
+        ));
+    }
+
     app.entities.insert(sniper(point![96.0, 16.0]));
     app.entities.insert(sniper(point![96.0, -16.0]));
 
@@ -63,8 +104,49 @@ async fn main() {
 
     app.entities.insert(turret_platform(point![128.0, 0.0]));
 
+    let frigate_index = app.entities.insert(missile_frigate(point![160.0, 48.0]));
+    for angle in [0.0, TAU / 2.0] {
+        app.entities.insert(drone(
+            frigate_index,
+            &app.entities[frigate_index],
+            angle,
+            Color::from_hex(0xff0000),
+            Team::HOSTILE,
+        ));
+    }
+
+    app.entities.insert(lancer(point![96.0, -64.0]));
+
+    app.entities.insert(carrier(point![192.0, -48.0]));
+
+    app.entities.insert(dreadnought(point![256.0, 96.0]));
+
     app.entities.insert(neutral(point![-128.0, 0.0]));
 
+    // A planet to slingshot shots around, with a derelict moon in orbit
+    let planet = gravity::Planet::from_strength(
+        point![0.0, 112.0],
+        16.0,
+        16384.0,
+        Color::from_hex(0x808080),
+    );
+    app.entities.insert(moon(&planet, 40.0));
+    app.gravity.planets.push(planet);
+
+    // Raiders are at war with everyone, so they fight the hostiles if the player lures them in
+    let raiders = app.factions.add();
+    app.factions
+        .set(raiders, Team::PLAYER, Relationship::Hostile);
+    app.factions
+        .set(raiders, Team::HOSTILE, Relationship::Hostile);
+
+    for position in [point![-160.0, 64.0], point![-160.0, -64.0]] {
+        let mut raider = berzerker(position);
+        raider.team = raiders;
+        raider.color = Color::from_hex(0xff8000);
+        app.entities.insert(raider);
+    }
+
     macroquad::input::show_mouse(false);
 
     let mut fullscreen = START_IN_FULLSCREEN;
@@ -75,11 +157,15 @@ async fn main() {
             set_fullscreen(fullscreen);
         }
 
+        if macroquad::input::is_key_pressed(KeyCode::G) {
+            app.release_satellites(player_index);
+        }
+
         if macroquad::input::is_key_pressed(KeyCode::O) {
             for (_, entity) in &mut app.entities {
-                let controller::Team::Hostile = entity.team else {
+                if app.factions.get(entity.team, Team::PLAYER) != Relationship::Hostile {
                     continue;
-                };
+                }
 
                 let Some(controller) = &mut entity.controller else {
                     continue;
@@ -120,24 +206,28 @@ pub fn sniper(position: Point2<f32>) -> Entity {
             shooting: Some(controller::ShootingController::Computer(
                 computer_controller::ComputerShootingController {
                     weapon: Weapon {
-                        initial_speed: 48.0,
-                        speed_exponent: 50.0,
                         cooldown: 2.0,
-                        projectiles_per_shot: 1,
-                        projectile_angle: 0.0,
-                        projectile_spread: 0.0,
-                        sight_kind: SightKind::Arrow,
-                        sight_size: 1.0,
+                        damage: Damage::from_amount(3, DamageKind::Kinetic),
+                        knockback: 24.0,
+                        projectile_size: vector![0.75, 6.0],
+                        lifetime: 1.25,
+                        fade_out: 0.25,
+                        status: Some(StatusEffect::from_duration(StatusKind::Stun, 0.3)),
+                        ..Default::default()
                     },
+                    beam: None,
                     aim: None,
                     cooldown: 0.0,
                     aiming_lead: 1.0,
                     lead_weight: 5.0,
+                    aim_kind: computer_controller::ComputerAimKind::Predictive,
+                    firing_kind: computer_controller::ComputerFiringKind::ClearLine,
                 },
             )),
         }),
-        controller::Team::Hostile,
+        Team::HOSTILE,
     )
+    .with_drops(DropTable::from_drops(vec![(0.5, REPAIR)]))
 }
 
 // strategy: keep distance or circle around
@@ -170,32 +260,47 @@ pub fn berzerker(position: Point2<f32>) -> Entity {
                     weapon: Weapon {
                         initial_speed: 48.0 * 5.0,
                         speed_exponent: 1.0 / 50.0,
-                        cooldown: 1.0,
                         projectiles_per_shot: 2,
-                        projectile_angle: 0.0,
                         projectile_spread: TAU / 32.0,
                         sight_kind: SightKind::Cross,
-                        sight_size: 1.0,
+                        knockback: 6.0,
+                        ..Default::default()
                     },
+                    beam: None,
                     aim: None,
                     cooldown: 0.0,
                     aiming_lead: 0.0,
                     lead_weight: 0.0,
+                    aim_kind: computer_controller::ComputerAimKind::PointTowards,
+                    firing_kind: computer_controller::ComputerFiringKind::WithinDistance {
+                        distance: 0.0..24.0,
+                    },
                 },
             )),
         }),
-        controller::Team::Hostile,
+        Team::HOSTILE,
     )
+    .with_drops(DropTable::from_drops(vec![
+        (0.25, REPAIR),
+        (0.25, PickupKind::RingPiece),
+    ]))
 }
 
 pub fn turret_platform(position: Point2<f32>) -> Entity {
-    entity::Entity::from_rings(
+    let mut platform = entity::Entity::from_rings(
         position,
         Color::from_hex(0xff0000),
         components::Center::from_size(vector![4.0, 4.0], 32, TAU / 6.0),
         vec![
             components::ArmorRing::from_size(vector![4.0, 1.0], 4, 4, 4.5, -TAU / 12.0),
-            components::ArmorRing::from_size(vector![16.0, 2.0], 32, 2, 8.0, TAU / 24.0),
+            components::ArmorRing::from_size(vector![16.0, 2.0], 32, 2, 8.0, TAU / 24.0)
+                .with_resistances(Resistances {
+                    explosive: 0.5,
+                    ..Resistances::NONE
+                })
+                .with_regeneration(components::Regeneration::from_rate(6.0, 4.0, 8.0))
+                .with_mount(mount::Mount::from_weapon(0, flak(), TAU / 4.0))
+                .with_mount(mount::Mount::from_weapon(1, flak(), TAU / 4.0)),
         ],
         Some(controller::EntityController {
             targets: Vec::new(),
@@ -206,21 +311,444 @@ pub fn turret_platform(position: Point2<f32>) -> Entity {
                         initial_speed: 48.0 * 15.0,
                         speed_exponent: 1.0 / 50.0,
                         cooldown: 0.1,
-                        projectiles_per_shot: 1,
-                        projectile_angle: 0.0,
                         projectile_spread: TAU / 16.0,
-                        sight_kind: SightKind::Arrow,
                         sight_size: 2.0,
+                        knockback: 2.0,
+                        ..Default::default()
+                    },
+                    beam: None,
+                    aim: None,
+                    cooldown: 0.0,
+                    aiming_lead: 0.0,
+                    lead_weight: 0.0,
+                    aim_kind: computer_controller::ComputerAimKind::Sweep {
+                        amplitude: TAU / 24.0,
+                        speed: 2.0,
+                        phase: 0.0,
+                    },
+                    firing_kind: computer_controller::ComputerFiringKind::Always,
+                },
+            )),
+        }),
+        Team::HOSTILE,
+    )
+    .with_drops(DropTable::from_drops(vec![
+        (1.0, OVERCHARGE),
+        (0.5, PickupKind::RingPiece),
+    ]));
+    // Heavy enough to bend shots that pass close by
+    platform.gravity = 4096.0;
+    platform
+}
+
+// light gun for ring mounts, too weak to carry an entity on its own
+pub fn flak() -> Weapon {
+    Weapon {
+        initial_speed: 48.0 * 6.0,
+        speed_exponent: 1.0 / 4.0,
+        cooldown: 0.4,
+        projectiles_per_shot: 3,
+        projectile_angle: TAU / 64.0,
+        projectile_spread: TAU / 128.0,
+        damage: Damage::from_amount(1, DamageKind::Kinetic),
+        knockback: 1.0,
+        projectile_size: vector![0.75, 1.5],
+        lifetime: 0.5,
+        ..Default::default()
+    }
+}
+
+// strategy: close the distance, the missiles can't turn fast enough to hit up close
+pub fn missile_frigate(position: Point2<f32>) -> Entity {
+    let mut frigate = entity::Entity::from_rings(
+        position,
+        Color::from_hex(0xff0000),
+        components::Center::from_size(vector![3.0, 2.0], 12, TAU / 4.0),
+        vec![
+            components::ArmorRing::from_size(vector![4.0, 1.0], 4, 4, 4.0, TAU / 12.0),
+            // Squashed into an ellipse, so the pieces bunch up at its left and right ends
+            components::ArmorRing::from_size(vector![3.0, 1.0], 2, 6, 7.0, -TAU / 24.0).with_orbit(
+                components::Orbit {
+                    aspect: 0.6,
+                    ..Default::default()
+                },
+            ),
+        ],
+        Some(controller::EntityController {
+            targets: Vec::new(),
+            motion: Some(controller::MotionController::Computer(
+                computer_controller::ComputerMotionController {
+                    speed: 12.0,
+                    kind: computer_controller::ComputerMotionControllerKind::KeepDistance {
+                        distance: 50.0..60.0,
                     },
+                },
+            )),
+            shooting: Some(controller::ShootingController::Computer(
+                computer_controller::ComputerShootingController {
+                    weapon: Weapon {
+                        initial_speed: 30.0,
+                        speed_exponent: 5.0,
+                        cooldown: 2.5,
+                        projectiles_per_shot: 2,
+                        projectile_angle: TAU / 4.0,
+                        projectile_spread: 0.0,
+                        sight_kind: SightKind::Cross,
+                        sight_size: 1.5,
+                        guidance: Some(Guidance::from_turn_rate(
+                            GuidanceKind::ProportionalNavigation { gain: 3.0 },
+                            TAU / 2.0,
+                            TAU / 8.0,
+                        )),
+                        interception: projectile::Interception {
+                            interceptable: true,
+                            point_defense: false,
+                        },
+                        damage: Damage::from_amount(3, DamageKind::Explosive),
+                        knockback: 16.0,
+                        blast_radius: 8.0,
+                        projectile_size: vector![1.5, 3.0],
+                        lifetime: 1.5,
+                        status: Some(StatusEffect::from_duration(
+                            StatusKind::Burn {
+                                damage_per_second: 2.0,
+                            },
+                            3.0,
+                        )),
+                        ..Default::default()
+                    },
+                    beam: None,
                     aim: None,
                     cooldown: 0.0,
                     aiming_lead: 0.0,
                     lead_weight: 0.0,
+                    aim_kind: computer_controller::ComputerAimKind::PointTowards,
+                    firing_kind: computer_controller::ComputerFiringKind::WithinDistance {
+                        distance: 0.0..80.0,
+                    },
+                },
+            )),
+        }),
+        Team::HOSTILE,
+    )
+    .with_drops(DropTable::from_drops(vec![
+        (0.5, PickupKind::RingPiece),
+        (0.5, OVERCHARGE),
+    ]));
+    // Beams overload the shield and explosions slip through it
+    frigate.shield = Some(components::Shield {
+        resistances: Resistances {
+            energy: 2.0,
+            ..Resistances::NONE
+        },
+        bypass: vec![DamageKind::Explosive],
+        ..components::Shield::from_capacity(8.0, 2.0, 3.0)
+    });
+    frigate
+}
+
+// strategy: kill the carrier first, its fighters blow up with it
+pub fn carrier(position: Point2<f32>) -> Entity {
+    entity::Entity::from_rings(
+        position,
+        Color::from_hex(0xff0000),
+        components::Center::from_size(vector![3.0, 3.0], 16, TAU / 8.0),
+        vec![
+            components::ArmorRing::from_size(vector![6.0, 1.5], 6, 6, 5.0, -TAU / 24.0),
+            components::ArmorRing::from_size(vector![4.0, 1.0], 10, 3, 8.0, TAU / 32.0),
+        ],
+        Some(controller::EntityController {
+            targets: Vec::new(),
+            motion: Some(controller::MotionController::Computer(
+                computer_controller::ComputerMotionController {
+                    speed: 8.0,
+                    kind: computer_controller::ComputerMotionControllerKind::KeepDistance {
+                        distance: 64.0..80.0,
+                    },
                 },
             )),
+            shooting: None,
         }),
-        controller::Team::Hostile,
+        Team::HOSTILE,
     )
+    .with_hangar(hangar::Hangar::from_archetype(fighter, 4, 2.0).with_self_destruct())
+    .with_drops(DropTable::from_drops(vec![
+        (1.0, PickupKind::RingPiece),
+        (0.5, OVERCHARGE),
+    ]))
+}
+
+// strategy: swat them as they swarm in, they barely have any armor
+pub fn fighter(position: Point2<f32>) -> Entity {
+    entity::Entity::from_rings(
+        position,
+        Color::from_hex(0xff0000),
+        components::Center::from_size(vector![1.0, 1.5], 2, TAU / 2.0),
+        vec![components::ArmorRing::from_size(
+            vector![1.5, 0.5],
+            3,
+            1,
+            2.0,
+            TAU / 4.0,
+        )],
+        Some(controller::EntityController {
+            targets: Vec::new(),
+            motion: Some(controller::MotionController::Computer(
+                computer_controller::ComputerMotionController {
+                    speed: 32.0,
+                    kind: computer_controller::ComputerMotionControllerKind::Circle {
+                        distance: 12.0,
+                        tangential_weight: rand::gen_range(-2.0, 2.0),
+                    },
+                },
+            )),
+            shooting: Some(controller::ShootingController::Computer(
+                computer_controller::ComputerShootingController {
+                    weapon: Weapon {
+                        initial_speed: 48.0 * 4.0,
+                        speed_exponent: 1.0 / 8.0,
+                        cooldown: 1.25,
+                        sight_kind: SightKind::Cross,
+                        sight_size: 0.5,
+                        projectile_size: vector![0.5, 1.5],
+                        lifetime: 0.4,
+                        ..Default::default()
+                    },
+                    beam: None,
+                    aim: None,
+                    cooldown: 0.0,
+                    aiming_lead: 1.0,
+                    lead_weight: 10.0,
+                    aim_kind: computer_controller::ComputerAimKind::PointTowards,
+                    firing_kind: computer_controller::ComputerFiringKind::Burst {
+                        shots: 2,
+                        interval: 0.1,
+                        remaining: 0,
+                    },
+                },
+            )),
+        }),
+        Team::HOSTILE,
+    )
+}
+
+// strategy: peel the rings off one at a time, each one lost makes it angrier
+pub fn dreadnought(position: Point2<f32>) -> Entity {
+    entity::Entity::from_rings(
+        position,
+        Color::from_hex(0xff0040),
+        components::Center::from_size(vector![4.0, 4.0], 24, TAU / 6.0),
+        vec![
+            components::ArmorRing::from_size(vector![4.0, 1.5], 8, 4, 5.0, TAU / 12.0),
+            components::ArmorRing::from_size(vector![4.0, 1.0], 6, 6, 8.0, -TAU / 16.0),
+            components::ArmorRing::from_size(vector![4.0, 1.0], 4, 10, 11.0, TAU / 24.0),
+        ],
+        Some(controller::EntityController {
+            targets: Vec::new(),
+            motion: Some(controller::MotionController::Computer(
+                computer_controller::ComputerMotionController {
+                    speed: 10.0,
+                    kind: computer_controller::ComputerMotionControllerKind::KeepDistance {
+                        distance: 40.0..56.0,
+                    },
+                },
+            )),
+            shooting: Some(controller::ShootingController::Computer(
+                computer_controller::ComputerShootingController {
+                    weapon: Weapon {
+                        initial_speed: 48.0 * 3.0,
+                        speed_exponent: 1.0 / 4.0,
+                        cooldown: 1.5,
+                        projectiles_per_shot: 3,
+                        projectile_angle: TAU / 48.0,
+                        sight_kind: SightKind::Cross,
+                        sight_size: 2.0,
+                        damage: Damage::from_amount(2, DamageKind::Kinetic),
+                        knockback: 12.0,
+                        projectile_size: vector![1.0, 3.0],
+                        lifetime: 1.0,
+                        ..Default::default()
+                    },
+                    beam: None,
+                    aim: None,
+                    cooldown: 0.0,
+                    aiming_lead: 1.0,
+                    lead_weight: 10.0,
+                    aim_kind: computer_controller::ComputerAimKind::PointTowards,
+                    firing_kind: computer_controller::ComputerFiringKind::Burst {
+                        shots: 3,
+                        interval: 0.15,
+                        remaining: 0,
+                    },
+                },
+            )),
+        }),
+        Team::HOSTILE,
+    )
+    .with_boss(
+        "Dreadnought",
+        vec![
+            // Outer ring lost: close in and spin up
+            boss::Phase {
+                motion: Some(computer_controller::ComputerMotionControllerKind::Circle {
+                    distance: 24.0,
+                    tangential_weight: 1.0,
+                }),
+                ring_speed_multiplier: 2.0,
+                minions: vec![fighter, fighter],
+                ..Default::default()
+            },
+            // Middle ring lost: charge in with a shotgun
+            boss::Phase {
+                motion: Some(computer_controller::ComputerMotionControllerKind::Charge),
+                weapon: Some(Weapon {
+                    initial_speed: 48.0 * 5.0,
+                    speed_exponent: 1.0 / 50.0,
+                    cooldown: 0.75,
+                    projectiles_per_shot: 7,
+                    projectile_spread: TAU / 16.0,
+                    sight_kind: SightKind::Cross,
+                    sight_size: 2.0,
+                    knockback: 8.0,
+                    lifetime: 0.5,
+                    ..Default::default()
+                }),
+                ring_speed_multiplier: 3.0,
+                minions: vec![fighter, fighter, fighter],
+            },
+        ],
+    )
+    .with_drops(DropTable::from_drops(vec![
+        (1.0, PickupKind::RingPiece),
+        (1.0, OVERCHARGE),
+        (1.0, REPAIR),
+    ]))
+}
+
+// strategy: stay out of reach, or bait it into overheating
+pub fn lancer(position: Point2<f32>) -> Entity {
+    entity::Entity::from_rings(
+        position,
+        Color::from_hex(0xff0000),
+        components::Center::from_size(vector![2.0, 3.0], 10, TAU / 4.0),
+        vec![
+            components::ArmorRing::from_size(vector![3.0, 1.0], 4, 3, 4.0, -TAU / 8.0),
+            // A heavy plate flanked by two lighter ones, with little cover behind
+            components::ArmorRing::from_slots(
+                &[
+                    components::RingSlot::from_width(-TAU / 8.0, TAU / 10.0, 1.0, 2),
+                    components::RingSlot::from_width(0.0, TAU / 8.0, 1.5, 4),
+                    components::RingSlot::from_width(TAU / 8.0, TAU / 10.0, 1.0, 2),
+                    components::RingSlot::from_width(TAU / 2.0, TAU / 16.0, 0.75, 1),
+                ],
+                6.5,
+                TAU / 16.0,
+            ),
+        ],
+        Some(controller::EntityController {
+            targets: Vec::new(),
+            motion: Some(controller::MotionController::Computer(
+                computer_controller::ComputerMotionController {
+                    speed: rand::gen_range(14.0, 16.0),
+                    kind: computer_controller::ComputerMotionControllerKind::KeepDistance {
+                        distance: 20.0..28.0,
+                    },
+                },
+            )),
+            shooting: Some(controller::ShootingController::Computer(
+                computer_controller::ComputerShootingController {
+                    weapon: Default::default(),
+                    beam: Some(Beam::from_damage(
+                        3.0,
+                        DamageKind::Energy,
+                        32.0,
+                        0.75,
+                        0.4,
+                        0.5,
+                    )),
+                    aim: None,
+                    cooldown: 0.0,
+                    aiming_lead: 0.0,
+                    lead_weight: 0.0,
+                    aim_kind: computer_controller::ComputerAimKind::PointTowards,
+                    firing_kind: computer_controller::ComputerFiringKind::Always,
+                },
+            )),
+        }),
+        Team::HOSTILE,
+    )
+    .with_drops(DropTable::from_drops(vec![(0.5, OVERCHARGE)]))
+}
+
+// strategy: shoot them off the parent first, or they scatter once it dies
+pub fn drone(parent_index: Index, parent: &Entity, angle: f32, color: Color, team: Team) -> Entity {
+    let satellite = satellite::Satellite::from_orbit(parent_index, 14.0, angle, TAU / 4.0);
+    let mut drone = entity::Entity::from_rings(
+        parent.position + util::displacement_from_angle(UnitComplex::new(angle), satellite.radius),
+        color,
+        components::Center::from_size(vector![1.5, 1.5], 4, TAU / 3.0),
+        vec![components::ArmorRing::from_size(
+            vector![2.0, 0.75],
+            2,
+            3,
+            2.5,
+            -TAU / 6.0,
+        )],
+        Some(controller::EntityController {
+            targets: Vec::new(),
+            motion: None,
+            shooting: Some(controller::ShootingController::Computer(
+                computer_controller::ComputerShootingController {
+                    weapon: Weapon {
+                        initial_speed: 48.0 * 6.0,
+                        speed_exponent: 1.0 / 4.0,
+                        cooldown: 0.75,
+                        sight_kind: SightKind::Cross,
+                        sight_size: 0.5,
+                        damage: Damage::from_amount(1, DamageKind::Kinetic),
+                        projectile_size: vector![0.5, 1.5],
+                        lifetime: 0.5,
+                        status: Some(StatusEffect::from_duration(
+                            StatusKind::Slow { multiplier: 0.5 },
+                            1.5,
+                        )),
+                        ..Default::default()
+                    },
+                    beam: None,
+                    aim: None,
+                    cooldown: 0.0,
+                    aiming_lead: 1.0,
+                    lead_weight: 10.0,
+                    aim_kind: computer_controller::ComputerAimKind::PointTowards,
+                    firing_kind: computer_controller::ComputerFiringKind::ClearLine,
+                },
+            )),
+        }),
+        team,
+    );
+    drone.satellite = Some(satellite);
+    drone
+}
+
+// strategy: use it as cover, it circles the planet on its own
+pub fn moon(planet: &gravity::Planet, distance: f32) -> Entity {
+    let mut moon = entity::Entity::from_rings(
+        planet.position + vector![0.0, -distance],
+        Color::from_hex(0x808080),
+        components::Center::from_size(vector![3.0, 3.0], 16, TAU / 24.0),
+        vec![components::ArmorRing::from_size(
+            vector![3.0, 1.5],
+            4,
+            6,
+            4.5,
+            -TAU / 48.0,
+        )],
+        None,
+        Team::NEUTRAL,
+    );
+    moon.velocity = vector![planet.orbital_speed(distance), 0.0];
+    moon.drag = 0.0;
+    moon
 }
 
 // strategy: keep distance or just don't aggro it
@@ -232,7 +760,12 @@ pub fn neutral(position: Point2<f32>) -> Entity {
         vec![
             components::ArmorRing::from_size(vector![2.0, 1.0], 2, 6, 3.5, TAU / 12.0),
             components::ArmorRing::from_size(vector![2.0, 1.0], 2, 12, 6.5, -TAU / 24.0),
-            components::ArmorRing::from_size(vector![12.0, 2.0], 24, 3, 9.0, TAU / 48.0),
+            components::ArmorRing::from_size(vector![12.0, 2.0], 24, 3, 9.0, TAU / 48.0)
+                .with_orbit(components::Orbit {
+                    breathing: 0.2,
+                    breathing_speed: TAU / 3.0,
+                    ..Default::default()
+                }),
         ],
         Some(controller::EntityController {
             targets: Vec::new(),
@@ -252,17 +785,32 @@ pub fn neutral(position: Point2<f32>) -> Entity {
                         cooldown: 3.0,
                         projectiles_per_shot: 9,
                         projectile_angle: TAU / 32.0 / 9.0,
-                        projectile_spread: 0.0,
                         sight_kind: SightKind::Cross,
-                        sight_size: 1.0,
+                        damage: Damage::from_amount(1, DamageKind::Energy),
+                        projectile_size: vector![1.5, 2.0],
+                        lifetime: 0.6,
+                        status: Some(StatusEffect::from_duration(
+                            StatusKind::Jam(JamTarget::Rings),
+                            2.0,
+                        )),
+                        ..Default::default()
                     },
+                    beam: None,
                     aim: None,
                     cooldown: 0.0,
                     aiming_lead: 0.5,
                     lead_weight: 0.0,
+                    aim_kind: computer_controller::ComputerAimKind::PointTowards,
+                    firing_kind: computer_controller::ComputerFiringKind::WithinDistance {
+                        distance: 0.0..48.0,
+                    },
                 },
             )),
         }),
-        controller::Team::Neutral,
+        Team::NEUTRAL,
     )
+    .with_drops(DropTable::from_drops(vec![
+        (1.0, REPAIR),
+        (1.0, PickupKind::RingPiece),
+    ]))
 }
@@ -0,0 +1,97 @@
+use macroquad::prelude::*;
+use nalgebra::{Point2, UnitComplex, vector};
+use thunderdome::Index;
+
+use crate::{app::App, computer_controller::Weapon, entity::Entity, util};
+
+/// A weapon attached to one slot of an armor ring. It orbits along with the ring, can only fire
+/// within an arc facing away from the entity, and is destroyed along with the armor in its slot.
+#[derive(Clone, Debug)]
+pub struct Mount {
+    pub slot: usize,
+    pub weapon: Weapon,
+    /// Half angle of the arc the mount can fire within
+    pub arc: f32,
+    pub cooldown: f32,
+    /// Angle the mount is pointing at, relative to straight outwards
+    pub aim: f32,
+}
+
+impl Mount {
+    pub fn from_weapon(slot: usize, weapon: Weapon, arc: f32) -> Self {
+        Self {
+            slot,
+            weapon,
+            arc,
+            cooldown: 0.0,
+            aim: 0.0,
+        }
+    }
+
+    /// Turns towards the closest target inside the arc and fires at it.
+    pub fn update(
+        &mut self,
+        ring: usize,
+        index: Index,
+        entity: &Entity,
+        targets: &[Index],
+        delta_seconds: f32,
+        app: &mut App,
+    ) {
+        self.cooldown = (self.cooldown - delta_seconds).max(0.0);
+
+        let ring = &entity.rings[ring];
+        let Some(armor) = ring.armor[self.slot] else {
+            return;
+        };
+
+        let (base, direction) = ring.get_slot_transform(self.slot);
+        let facing = direction.angle();
+        let origin = entity.position + base + direction * vector![armor.size.y, 0.0];
+
+        let aim = targets
+            .iter()
+            .filter_map(|&target| app.entities.get(target))
+            .map(|target| target.position - origin)
+            .filter_map(|displacement| {
+                let aim = util::angle_difference(displacement.y.atan2(displacement.x), facing);
+                (aim.abs() <= self.arc).then(|| (aim, util::length_squared(displacement)))
+            })
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .map(|(aim, _)| aim);
+
+        let Some(aim) = aim else {
+            return;
+        };
+        self.aim = aim;
+
+        if self.cooldown <= 0.0 {
+            self.cooldown = self.weapon.cooldown;
+            self.weapon.fire_from(
+                UnitComplex::new(facing + aim),
+                origin,
+                1.0,
+                index,
+                entity,
+                None,
+                app,
+            );
+        }
+    }
+
+    /// Draws the barrel at the outer end of the armor piece in `slot`.
+    pub fn draw(&self, position: Point2<f32>, facing: f32, color: Color) {
+        let angle = facing + self.aim;
+        draw_rectangle_ex(
+            position.x,
+            position.y,
+            1.5,
+            0.5,
+            DrawRectangleParams {
+                offset: vec2(0.0, 0.5),
+                rotation: angle,
+                color,
+            },
+        );
+    }
+}
@@ -1,13 +1,14 @@
 use macroquad::prelude::*;
 use nalgebra::{Point2, UnitComplex, point};
 
-use crate::components::ArmorRing;
+use crate::{components::ArmorRing, util};
 
 pub struct MouseDisplay {
     pub radius: f32,
     pub center_angle: f32,
     pub center_speed: f32,
     pub ring_angle: f32,
+    /// Commanded spin of the ring, previewed by faint corners ahead of the real ones
     pub ring_speed: f32,
     pub position: Point2<f32>,
     pub color: Color,
@@ -17,6 +18,9 @@ pub struct MouseDisplay {
 }
 
 impl MouseDisplay {
+    /// Seconds of commanded spin the preview corners lead the ring by
+    pub const SPIN_PREVIEW: f32 = 0.25;
+
     pub fn from_speed(center_speed: f32, ring_speed: f32) -> Self {
         let radius = 0.0;
         let center_angle = 0.0;
@@ -45,17 +49,26 @@ impl MouseDisplay {
         self.position = point![position.x, position.y];
     }
 
+    /// Each corner shows the most recently hit piece in its quarter of the ring, or is left empty
+    /// if that quarter has no armor left.
     pub fn set_effects_from_ring(&mut self, ring: &ArmorRing) {
-        for (armor, effect) in (&ring.armor)
-            .into_iter()
-            .rev()
-            .zip(&mut self.corner_effects)
-        {
-            if let Some(armor) = armor {
-                *effect = armor.hit_effect;
-            } else {
-                *effect = u16::MAX;
-            }
+        use std::f32::consts::TAU;
+
+        for (i, effect) in self.corner_effects.iter_mut().enumerate() {
+            // The corners go clockwise starting a quarter turn behind the ring angle
+            let corner_angle = -(i as f32 + 1.0) * TAU / 4.0;
+
+            *effect = ring
+                .armor
+                .iter()
+                .zip(&ring.offsets)
+                .filter_map(|(armor, &offset)| {
+                    let difference = util::angle_difference(offset, corner_angle);
+                    ((-TAU / 8.0..TAU / 8.0).contains(&difference))
+                        .then_some(armor.as_ref()?.hit_effect)
+                })
+                .max()
+                .unwrap_or(u16::MAX);
         }
     }
 
@@ -85,8 +98,30 @@ impl MouseDisplay {
 
         let size_boost = self.size_boost as f32 / u16::MAX as f32 * 1.25;
 
-        let angle = UnitComplex::new(self.ring_angle + TAU / 8.0);
         let radius = self.radius + 0.5 + (size_boost * SQRT_2 / 2.0);
+
+        let lead = (self.ring_speed * Self::SPIN_PREVIEW).clamp(-TAU / 8.0, TAU / 8.0);
+        if lead != 0.0 {
+            let angle = UnitComplex::new(self.ring_angle + lead + TAU / 8.0);
+            for (x, y) in get_rotations_of(angle.re * radius, angle.im * radius) {
+                draw_rectangle_ex(
+                    self.position.x + x,
+                    self.position.y + y,
+                    0.5,
+                    0.5,
+                    DrawRectangleParams {
+                        offset: vec2(0.5, 0.5),
+                        rotation: self.ring_angle + lead,
+                        color: Color {
+                            a: 0.3,
+                            ..self.color
+                        },
+                    },
+                );
+            }
+        }
+
+        let angle = UnitComplex::new(self.ring_angle + TAU / 8.0);
         for (i, (x, y)) in get_rotations_of(angle.re * radius, angle.im * radius)
             .into_iter()
             .enumerate()
@@ -0,0 +1,151 @@
+use macroquad::{prelude::*, rand};
+use nalgebra::{Point2, UnitComplex, Vector2};
+use std::ops::Range;
+
+use crate::util;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Particle {
+    pub position: Point2<f32>,
+    pub velocity: Vector2<f32>,
+    pub size: f32,
+    pub color: Color,
+    pub age: f32,
+    pub lifetime: f32,
+}
+
+impl Particle {
+    pub const DRAG: f32 = 0.9;
+
+    pub fn update(&mut self, delta_seconds: f32) {
+        util::drift(
+            &mut self.position,
+            &mut self.velocity,
+            Vector2::zeros(),
+            Self::DRAG,
+            delta_seconds,
+        );
+        self.age += delta_seconds;
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.age >= self.lifetime
+    }
+
+    pub fn draw(&self) {
+        let size = self.size * (1.0 - 0.5 * self.age / self.lifetime);
+        draw_rectangle(
+            self.position.x - size / 2.0,
+            self.position.y - size / 2.0,
+            size,
+            size,
+            Color {
+                a: self.color.a * (1.0 - self.age / self.lifetime),
+                ..self.color
+            },
+        );
+    }
+}
+
+/// Describes a burst of particles thrown out in a cone.
+#[derive(Clone, Debug)]
+pub struct Emitter {
+    pub count: usize,
+    pub speed: Range<f32>,
+    /// Half angle of the cone, PI throws particles in every direction
+    pub spread: f32,
+    pub lifetime: Range<f32>,
+    pub size: f32,
+}
+
+impl Emitter {
+    pub const DEATH: Self = Self {
+        count: 48,
+        speed: 8.0..40.0,
+        spread: std::f32::consts::PI,
+        lifetime: 0.5..1.25,
+        size: 0.75,
+    };
+    pub const ARMOR: Self = Self {
+        count: 8,
+        speed: 4.0..16.0,
+        spread: std::f32::consts::PI,
+        lifetime: 0.25..0.5,
+        size: 0.5,
+    };
+    pub const IMPACT: Self = Self {
+        count: 4,
+        speed: 8.0..24.0,
+        spread: std::f32::consts::PI / 4.0,
+        lifetime: 0.1..0.25,
+        size: 0.375,
+    };
+    pub const MUZZLE_FLASH: Self = Self {
+        count: 3,
+        speed: 16.0..32.0,
+        spread: std::f32::consts::PI / 8.0,
+        lifetime: 0.05..0.15,
+        size: 0.5,
+    };
+
+    /// Throws particles out around `direction`, on top of the velocity of whatever emitted them.
+    pub fn emit(
+        &self,
+        position: Point2<f32>,
+        direction: UnitComplex<f32>,
+        velocity: Vector2<f32>,
+        color: Color,
+        particles: &mut Particles,
+    ) {
+        for _ in 0..self.count {
+            let angle =
+                UnitComplex::new(direction.angle() + rand::gen_range(-1.0, 1.0) * self.spread);
+            let speed = rand::gen_range(self.speed.start, self.speed.end);
+
+            particles.insert(Particle {
+                position,
+                velocity: velocity + util::displacement_from_angle(angle, speed),
+                size: self.size,
+                color,
+                age: 0.0,
+                lifetime: rand::gen_range(self.lifetime.start, self.lifetime.end),
+            });
+        }
+    }
+}
+
+/// Every live particle. Once `budget` particles are live, new ones are dropped until old ones
+/// expire, so large fights can't tank the frame rate.
+#[derive(Clone, Debug)]
+pub struct Particles {
+    pub particles: Vec<Particle>,
+    pub budget: usize,
+}
+
+impl Particles {
+    pub fn with_budget(budget: usize) -> Self {
+        Self {
+            particles: Vec::with_capacity(budget),
+            budget,
+        }
+    }
+
+    pub fn insert(&mut self, particle: Particle) {
+        if self.particles.len() < self.budget {
+            self.particles.push(particle);
+        }
+    }
+
+    pub fn update(&mut self, delta_seconds: f32) {
+        for particle in &mut self.particles {
+            particle.update(delta_seconds);
+        }
+        self.particles.retain(|particle| !particle.is_expired());
+    }
+
+    pub fn draw(&self) {
+        for particle in &self.particles {
+            particle.draw();
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use macroquad::{prelude::*, rand};
+use nalgebra::{Point2, Vector2, vector};
+
+use crate::{controller::ShootingController, damage::Damage, entity::Entity, util};
+
+#[derive(Clone, Copy, Debug)]
+pub enum PickupKind {
+    /// Restores health to every damaged piece on the collector's rings
+    Repair { amount: u16 },
+    /// Regrows a destroyed slot on the collector's rings at full health
+    RingPiece,
+    /// Temporarily improves the collector's weapon
+    WeaponBuff(WeaponBuff),
+}
+
+impl PickupKind {
+    pub fn color(&self) -> Color {
+        match self {
+            Self::Repair { .. } => Color::from_hex(0x00ff80),
+            Self::RingPiece => Color::from_hex(0x00c0ff),
+            Self::WeaponBuff(_) => Color::from_hex(0xffc000),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct WeaponBuff {
+    /// Multiplier applied to the time between shots
+    pub cooldown_multiplier: f32,
+    pub damage_multiplier: f32,
+    pub duration: f32,
+}
+
+impl WeaponBuff {
+    pub fn apply(&self, damage: Damage) -> Damage {
+        damage.scaled(self.damage_multiplier)
+    }
+}
+
+/// An item dropped by a destroyed entity, collected by flying the player over it.
+#[derive(Clone, Copy, Debug)]
+pub struct Pickup {
+    pub position: Point2<f32>,
+    pub velocity: Vector2<f32>,
+    pub kind: PickupKind,
+    pub age: f32,
+    pub lifetime: f32,
+}
+
+impl Pickup {
+    pub const LIFETIME: f32 = 12.0;
+    /// The pickup blinks for this many seconds before despawning
+    pub const BLINK_TIME: f32 = 3.0;
+    pub const RADIUS: f32 = 1.5;
+    pub const DRAG: f32 = 0.75;
+    /// Maximum speed the pickup is scattered away from where it dropped at
+    pub const SCATTER_SPEED: f32 = 12.0;
+
+    pub fn from_kind(position: Point2<f32>, velocity: Vector2<f32>, kind: PickupKind) -> Self {
+        Self {
+            position,
+            velocity,
+            kind,
+            age: 0.0,
+            lifetime: Self::LIFETIME,
+        }
+    }
+
+    pub fn update(&mut self, gravity: Vector2<f32>, delta_seconds: f32) {
+        util::drift(
+            &mut self.position,
+            &mut self.velocity,
+            gravity,
+            Self::DRAG,
+            delta_seconds,
+        );
+        self.age += delta_seconds;
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.age >= self.lifetime
+    }
+
+    pub fn is_touching(&self, entity: &Entity) -> bool {
+        util::length_squared(entity.position - self.position)
+            < (entity.radius + Self::RADIUS).powi(2)
+    }
+
+    pub fn apply(&self, entity: &mut Entity) {
+        match self.kind {
+            PickupKind::Repair { amount } => {
+                for armor in entity
+                    .rings
+                    .iter_mut()
+                    .flat_map(|ring| ring.armor.iter_mut().flatten())
+                {
+                    armor.heal(amount);
+                }
+            }
+            PickupKind::RingPiece => {
+                let destroyed = entity.rings.iter_mut().find_map(|ring| {
+                    let slot = ring.armor.iter().position(Option::is_none)?;
+                    Some((ring, slot))
+                });
+
+                if let Some((ring, slot)) = destroyed {
+                    ring.regrow(slot, ring.blueprints[slot].max_health);
+                    entity.update_shape();
+                }
+            }
+            PickupKind::WeaponBuff(buff) => {
+                if let Some(ShootingController::Player(controller)) = entity
+                    .controller
+                    .as_mut()
+                    .and_then(|controller| controller.shooting.as_mut())
+                {
+                    controller.buff = Some(buff);
+                }
+            }
+        }
+    }
+
+    pub fn draw(&self) {
+        let remaining = self.lifetime - self.age;
+        if remaining < Self::BLINK_TIME && (remaining * 4.0).fract() < 0.5 {
+            return;
+        }
+
+        let pulse = 1.0 + 0.25 * (self.age * 4.0).sin();
+        draw_rectangle_ex(
+            self.position.x,
+            self.position.y,
+            Self::RADIUS * pulse,
+            Self::RADIUS * pulse,
+            DrawRectangleParams {
+                offset: vec2(0.5, 0.5),
+                rotation: std::f32::consts::TAU / 8.0,
+                color: self.kind.color(),
+            },
+        );
+    }
+}
+
+/// What an archetype can drop when destroyed. Every entry is rolled separately.
+#[derive(Clone, Debug, Default)]
+pub struct DropTable {
+    /// Chance from 0.0 to 1.0, along with what drops
+    pub drops: Vec<(f32, PickupKind)>,
+}
+
+impl DropTable {
+    pub fn from_drops(drops: Vec<(f32, PickupKind)>) -> Self {
+        Self { drops }
+    }
+
+    pub fn roll(&self, position: Point2<f32>, velocity: Vector2<f32>, pickups: &mut Vec<Pickup>) {
+        for &(chance, kind) in &self.drops {
+            if rand::gen_range(0.0, 1.0) >= chance {
+                continue;
+            }
+
+            let scatter = vector![rand::gen_range(-1.0, 1.0), rand::gen_range(-1.0, 1.0)]
+                * Pickup::SCATTER_SPEED;
+            pickups.push(Pickup::from_kind(position, velocity + scatter, kind));
+        }
+    }
+}
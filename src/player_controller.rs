@@ -1,12 +1,20 @@
 use crate::{
     app::App,
+    beam::Beam,
+    computer_controller::Weapon,
+    damage::DamageKind,
     entity::Entity,
+    gravity::Gravity,
     input::{InputAxis, InputButton},
+    pickup::WeaponBuff,
+    projectile::{Interception, Projectiles},
     util,
 };
 use macroquad::{
+    color::Color,
     input::{KeyCode, MouseButton},
     rand,
+    shapes::draw_circle,
 };
 use nalgebra::{Complex, UnitComplex, vector};
 use std::ops::Range;
@@ -17,10 +25,21 @@ pub struct PlayerMotionController {
     pub x_control: InputAxis,
     pub y_control: InputAxis,
     pub speed: f32,
+    /// Positive spins the rings faster, negative slows them down to a stop
+    pub spin_control: InputAxis,
+    pub reverse_control: Vec<InputButton>,
+    pub lock_control: Vec<InputButton>,
+    /// Change in commanded ring speed per second while `spin_control` is held
+    pub spin_rate: f32,
+    pub max_spin: f32,
+    /// Presses of `reverse_control` and `lock_control` seen since the last timestep. Key presses
+    /// only register for one frame, which can run any number of timesteps.
+    pub reverse_pending: bool,
+    pub lock_pending: bool,
 }
 
 impl PlayerMotionController {
-    pub fn update(&mut self, entity: &mut Entity) {
+    pub fn update(&mut self, entity: &mut Entity, delta_seconds: f32) {
         self.x_control.update_state();
         self.y_control.update_state();
         let input = vector![self.x_control.as_f32(), self.y_control.as_f32()];
@@ -29,7 +48,38 @@ impl PlayerMotionController {
         } else {
             input.normalize()
         };
-        entity.velocity = input * self.speed;
+        entity.thrust_towards(input * self.speed, delta_seconds);
+
+        self.update_spin(entity, delta_seconds);
+    }
+
+    /// Records presses of the toggle controls. This must be called once per frame, so a press is
+    /// applied exactly once by the next timestep.
+    pub fn latch_presses(&mut self) {
+        self.reverse_pending |= self.reverse_control.iter().any(|b| b.is_pressed());
+        self.lock_pending |= self.lock_control.iter().any(|b| b.is_pressed());
+    }
+
+    /// Commands the ring speeds. The rings accelerate towards the command on their own.
+    pub fn update_spin(&mut self, entity: &mut Entity, delta_seconds: f32) {
+        self.spin_control.update_state();
+        let change = self.spin_control.as_f32() * self.spin_rate * delta_seconds;
+        let reverse = std::mem::take(&mut self.reverse_pending);
+        let lock = std::mem::take(&mut self.lock_pending);
+
+        for ring in &mut entity.rings {
+            // The sign of a stopped ring is kept as -0.0, so it can still be reversed
+            let direction = ring.target_speed.signum();
+            let magnitude = (ring.target_speed.abs() + change).clamp(0.0, self.max_spin);
+            ring.target_speed = direction * magnitude;
+
+            if reverse {
+                ring.target_speed = -ring.target_speed;
+            }
+            if lock {
+                ring.locked ^= true;
+            }
+        }
     }
 }
 
@@ -45,6 +95,13 @@ impl Default for PlayerMotionController {
                 vec![KeyCode::W.into(), KeyCode::Up.into()],
             ),
             speed: 36.0,
+            spin_control: InputAxis::from_inputs(vec![KeyCode::E.into()], vec![KeyCode::Q.into()]),
+            reverse_control: vec![InputButton::Keyboard(KeyCode::R)],
+            lock_control: vec![InputButton::Keyboard(KeyCode::X)],
+            spin_rate: std::f32::consts::TAU / 4.0,
+            max_spin: std::f32::consts::TAU,
+            reverse_pending: false,
+            lock_pending: false,
         }
     }
 }
@@ -53,15 +110,23 @@ impl Default for PlayerMotionController {
 pub struct PlayerShootingController {
     pub shoot_control: Vec<InputButton>,
     pub precise_shoot_control: Vec<InputButton>,
+    pub beam_control: Vec<InputButton>,
     pub cooldown: f32,
     pub state: f32,
     pub speed: Range<f32>,
     pub precision: Range<f32>,
     pub delay: Range<f32>,
     pub aim: UnitComplex<f32>,
+    /// Only the projectile properties are used, the cooldown comes from `speed` instead
+    pub weapon: Weapon,
+    pub beam: Option<Beam>,
+    pub buff: Option<WeaponBuff>,
 }
 
 impl PlayerShootingController {
+    /// Number of timesteps between the dots of the trajectory preview
+    pub const TRAJECTORY_DOT_SPACING: usize = 4;
+
     pub fn update(&mut self, index: Index, entity: &mut Entity, delta_seconds: f32, app: &mut App) {
         let shoot_input = self.shoot_control.iter().any(|b| b.is_down());
         let precise_shoot_input = self.precise_shoot_control.iter().any(|b| b.is_down());
@@ -81,16 +146,26 @@ impl PlayerShootingController {
                 aim.angle() + rand::gen_range(-1.0, 1.0) * util::lerp(&self.precision, self.state),
             );
 
-            app.insert_projectile(
-                48.0,
-                50.0,
-                nudged_aim,
-                entity.position,
-                entity.radius + 4.0,
-                entity.color,
-                entity.team,
-                index,
-            );
+            match &self.buff {
+                Some(buff) => Weapon {
+                    damage: buff.apply(self.weapon.damage),
+                    ..self.weapon.clone()
+                }
+                .fire(nudged_aim, index, entity, None, app),
+                None => self.weapon.fire(nudged_aim, index, entity, None, app),
+            }
+        }
+
+        if let Some(buff) = &mut self.buff {
+            buff.duration -= delta_seconds;
+            if buff.duration <= 0.0 {
+                self.buff = None;
+            }
+        }
+
+        if let Some(beam) = &mut self.beam {
+            let beam_input = self.beam_control.iter().any(|b| b.is_down());
+            beam.update(beam_input.then_some(aim), index, entity, delta_seconds, app);
         }
 
         self.state += delta_seconds
@@ -105,11 +180,13 @@ impl PlayerShootingController {
         use std::f32::consts::TAU;
         app.mouse.center_angle = entity.center.angle;
         app.mouse.center_effect = entity.center.hit_effect;
-        if let Some(ring) = entity.rings.get(0) {
+        if let Some(ring) = entity.rings.first() {
             app.mouse.ring_angle = ring.angle - TAU * 3.0 / 8.0;
+            app.mouse.ring_speed = ring.get_commanded_speed();
             app.mouse.set_effects_from_ring(ring);
         } else {
             app.mouse.ring_angle = entity.center.angle * -0.5 - (TAU * 3.0 / 8.0);
+            app.mouse.ring_speed = 0.0;
             app.mouse.set_effects_from_empty_ring();
         }
         app.mouse.radius =
@@ -119,8 +196,48 @@ impl PlayerShootingController {
         app.mouse.size_boost = (self.cooldown * 1.0 * u16::MAX as f32) as u16;
     }
 
+    /// Draws the path a shot fired straight at the mouse would take through the gravity wells.
+    pub fn draw_trajectory(&self, entity: &Entity, gravity: &Gravity, delta_seconds: f32) {
+        if gravity.is_empty() {
+            return;
+        }
+
+        let mut angle = self.aim;
+        let mut speed = self.weapon.initial_speed;
+        let speed_exp_log = self.weapon.speed_exponent.ln();
+        let mut position =
+            entity.position + util::displacement_from_angle(angle, entity.radius + 4.0);
+
+        let steps = (self.weapon.lifetime / delta_seconds) as usize;
+        for step in 0..steps {
+            let acceleration = gravity.acceleration_at(position, None);
+            Projectiles::bend(&mut angle, &mut speed, acceleration, delta_seconds);
+            position += util::displacement_from_angle(
+                angle,
+                Projectiles::advance_speed(&mut speed, speed_exp_log, delta_seconds),
+            );
+
+            if gravity.is_inside_planet(position) {
+                break;
+            }
+
+            if step % Self::TRAJECTORY_DOT_SPACING == 0 {
+                draw_circle(
+                    position.x,
+                    position.y,
+                    0.375,
+                    Color {
+                        a: 0.5 * (1.0 - step as f32 / steps as f32),
+                        ..entity.color
+                    },
+                );
+            }
+        }
+    }
+
     pub fn max_cooldown(&self) -> f32 {
-        util::lerp(&self.speed, self.state)
+        let multiplier = self.buff.map_or(1.0, |buff| buff.cooldown_multiplier);
+        util::lerp(&self.speed, self.state) * multiplier
     }
 }
 
@@ -136,12 +253,32 @@ impl Default for PlayerShootingController {
                 InputButton::Keyboard(KeyCode::LeftAlt),
                 InputButton::Mouse(MouseButton::Left),
             ],
+            beam_control: vec![
+                InputButton::Keyboard(KeyCode::F),
+                InputButton::Mouse(MouseButton::Middle),
+            ],
             cooldown: 0.0,
             state: 0.0,
             speed: 0.5..0.25,
             precision: 0.0..0.15,
             delay: 1.0..2.0,
             aim: Default::default(),
+            weapon: Weapon {
+                interception: Interception {
+                    interceptable: false,
+                    point_defense: true,
+                },
+                ..Default::default()
+            },
+            beam: Some(Beam::from_damage(
+                4.0,
+                DamageKind::Energy,
+                48.0,
+                0.5,
+                0.5,
+                0.25,
+            )),
+            buff: None,
         }
     }
 }
@@ -1,184 +1,558 @@
-use crate::{app::App, collision::Rectangle, components::Armor, controller::Team, entity::Entity};
+use crate::{
+    app::App,
+    collision::{Rectangle, SpatialGrid},
+    components::ArmorSlot,
+    computer_controller::Weapon,
+    damage::Damage,
+    debris::Debris,
+    entity::Entity,
+    faction::{Factions, Relationship, Team},
+    guidance::Guidance,
+    particle::Emitter,
+    status::StatusEffect,
+    util,
+};
 use macroquad::prelude::*;
-use nalgebra::{Point2, UnitComplex, Vector2, distance_squared, vector};
+use nalgebra::{Point2, UnitComplex, Vector2, distance, vector};
 use thunderdome::Index;
 
+/// Every live projectile, stored as parallel columns so motion can be integrated for all of them
+/// in one tight loop. Despawning swaps the last projectile into the freed slot, so nothing is
+/// allocated once the columns have grown to the peak number of projectiles.
+#[derive(Clone, Debug)]
+pub struct Projectiles {
+    pub positions: Vec<Point2<f32>>,
+    pub angles: Vec<UnitComplex<f32>>,
+    /// The current speed, which is multiplied by the speed exponent every second
+    pub speeds: Vec<f32>,
+    /// Natural logarithm of the speed exponent base, 0.0 for constant speed
+    pub speed_exp_logs: Vec<f32>,
+    pub ages: Vec<f32>,
+    pub lifetimes: Vec<f32>,
+    pub previous_displacements: Vec<f32>,
+    /// Everything that isn't needed to move a projectile
+    pub details: Vec<ProjectileDetails>,
+    /// Interceptable projectiles by the center of their collider after moving this timestep
+    pub interceptable: SpatialGrid<usize>,
+    /// The largest collider radius in `interceptable`, which queries have to reach past
+    pub interceptable_reach: f32,
+    /// Live debris by position, rebuilt every timestep when debris provides cover
+    pub debris_grid: SpatialGrid<usize>,
+    pub debris_reach: f32,
+}
+
 #[derive(Clone, Copy, Debug)]
-pub struct Projectile {
-    pub position: Point2<f32>,
-    pub angle: UnitComplex<f32>,
-    pub initial_speed: f32,
-    pub speed_exp_base: f32,
-    pub lifetime: f32,
-    pub age: f32,
+pub struct ProjectileDetails {
     pub size: Vector2<f32>,
     pub color: Color,
+    pub fade_in: f32,
+    pub fade_out: f32,
     pub sender: Index,
     pub team: Option<Team>,
-    pub previous_displacement: f32,
+    pub guidance: Option<Guidance>,
+    pub interception: Interception,
+    pub damage: Damage,
+    pub knockback: f32,
+    pub blast_radius: f32,
+    pub status: Option<StatusEffect>,
 }
 
-impl Projectile {
-    pub fn from_speed(
-        initial_speed: f32,
-        speed_multiplier: f32,
+impl Projectiles {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            positions: Vec::with_capacity(capacity),
+            angles: Vec::with_capacity(capacity),
+            speeds: Vec::with_capacity(capacity),
+            speed_exp_logs: Vec::with_capacity(capacity),
+            ages: Vec::with_capacity(capacity),
+            lifetimes: Vec::with_capacity(capacity),
+            previous_displacements: Vec::with_capacity(capacity),
+            details: Vec::with_capacity(capacity),
+            interceptable: SpatialGrid::from_cell_size(16.0),
+            interceptable_reach: 0.0,
+            debris_grid: SpatialGrid::from_cell_size(16.0),
+            debris_reach: 0.0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn insert_from_weapon(
+        &mut self,
+        weapon: &Weapon,
         angle: UnitComplex<f32>,
         position: Point2<f32>,
-        size: Vector2<f32>,
-        lifetime: f32,
         color: Color,
         sender: Index,
         team: Option<Team>,
-    ) -> Self {
-        let age = 0.0;
-        Self {
-            position,
-            angle,
-            initial_speed,
-            speed_exp_base: speed_multiplier,
-            lifetime,
-            age,
-            size,
-            color,
+        guidance: Option<Guidance>,
+    ) {
+        self.positions.push(position);
+        self.angles.push(angle);
+        self.speeds.push(weapon.initial_speed);
+        self.speed_exp_logs.push(weapon.speed_exponent.ln());
+        self.ages.push(0.0);
+        self.lifetimes.push(weapon.lifetime);
+        self.previous_displacements.push(0.0);
+        self.details.push(ProjectileDetails {
+            size: weapon.projectile_size,
+            color: weapon.color.unwrap_or(color),
+            fade_in: weapon.fade_in,
+            fade_out: weapon.fade_out,
             sender,
             team,
-            previous_displacement: 0.0,
-        }
+            guidance,
+            interception: weapon.interception,
+            damage: weapon.damage,
+            knockback: weapon.knockback,
+            blast_radius: weapon.blast_radius,
+            status: weapon.status,
+        });
     }
 
-    /// Returning `None` indicates a request for deletion.
-    pub fn update(&mut self, delta_seconds: f32, app: &mut App) -> Option<()> {
-        let previous_age = self.age;
+    pub fn swap_remove(&mut self, i: usize) {
+        self.positions.swap_remove(i);
+        self.angles.swap_remove(i);
+        self.speeds.swap_remove(i);
+        self.speed_exp_logs.swap_remove(i);
+        self.ages.swap_remove(i);
+        self.lifetimes.swap_remove(i);
+        self.previous_displacements.swap_remove(i);
+        self.details.swap_remove(i);
+    }
 
-        self.age += delta_seconds;
-        if self.age >= self.lifetime {
-            return None;
+    pub fn update(&mut self, delta_seconds: f32, app: &mut App) {
+        // Guidance
+        for (i, details) in self.details.iter_mut().enumerate() {
+            if let Some(guidance) = &mut details.guidance {
+                guidance.steer(
+                    self.positions[i],
+                    &mut self.angles[i],
+                    details.team,
+                    delta_seconds,
+                    app,
+                );
+            }
+        }
+
+        // Gravity
+        if !app.gravity.is_empty() {
+            for (i, details) in self.details.iter().enumerate() {
+                let acceleration = app
+                    .gravity
+                    .acceleration_at(self.positions[i], Some(details.sender));
+                Self::bend(
+                    &mut self.angles[i],
+                    &mut self.speeds[i],
+                    acceleration,
+                    delta_seconds,
+                );
+            }
         }
 
         // Motion
-        if self.speed_exp_base == 1.0 {
-            self.position += self.velocity() * delta_seconds;
+        Self::integrate(
+            &mut self.positions,
+            &self.angles,
+            &mut self.speeds,
+            &self.speed_exp_logs,
+            &mut self.ages,
+            &mut self.previous_displacements,
+            delta_seconds,
+        );
 
-            self.previous_displacement = self.initial_speed * delta_seconds;
-        } else {
-            let displacement = self.initial_speed
-                * (self.speed_exp_base.powf(self.age) - self.speed_exp_base.powf(previous_age))
-                / self.speed_exp_base.ln();
-            self.position += self.distance_ahead(displacement);
+        // Broadphase, built after moving so colliders stretched by fast projectiles are covered
+        self.interceptable.clear();
+        self.interceptable_reach = 0.0;
+        for i in 0..self.len() {
+            if self.details[i].interception.interceptable {
+                let collider = self.get_collider(i);
+                self.interceptable.insert(collider.center(), i);
+                self.interceptable_reach = self
+                    .interceptable_reach
+                    .max(collider.radius_squared().sqrt());
+            }
+        }
 
-            self.previous_displacement = displacement;
+        self.debris_grid.clear();
+        self.debris_reach = 0.0;
+        if app.collision_rules.debris_cover {
+            for (i, piece) in app.debris.iter().enumerate() {
+                self.debris_grid.insert(piece.position, i);
+                self.debris_reach = self.debris_reach.max(piece.radius_squared().sqrt());
+            }
         }
 
         // Collision
-        let collider = self.get_collider();
-
-        let mut hit = None;
-        for (index, entity) in &mut app.entities {
-            if Some(entity.team) != self.team
-                && self
-                    .check_collisions_with_entity(&collider, entity, self.angle)
-                    .is_some()
+        for i in 0..self.len() {
+            if self.is_expired(i) {
+                continue;
+            }
+
+            if app.gravity.is_inside_planet(self.positions[i])
+                || self.check_collisions(i, app).is_none()
             {
-                hit = Some(index);
-                break;
+                self.ages[i] = self.lifetimes[i];
             }
         }
-        if let Some(hit) = hit {
-            if app.entities[hit].check_deletion().is_none() {
-                app.entities.remove(hit);
+
+        // Going backwards means the projectile swapped into a freed slot was already checked
+        for i in (0..self.len()).rev() {
+            if self.is_expired(i) {
+                self.swap_remove(i);
             }
+        }
+    }
+
+    /// Moves every projectile along its angle, following the exponential speed model.
+    fn integrate(
+        positions: &mut [Point2<f32>],
+        angles: &[UnitComplex<f32>],
+        speeds: &mut [f32],
+        speed_exp_logs: &[f32],
+        ages: &mut [f32],
+        previous_displacements: &mut [f32],
+        delta_seconds: f32,
+    ) {
+        for (((((position, angle), speed), &speed_exp_log), age), previous_displacement) in
+            positions
+                .iter_mut()
+                .zip(angles)
+                .zip(speeds)
+                .zip(speed_exp_logs)
+                .zip(ages)
+                .zip(previous_displacements)
+        {
+            let displacement = Self::advance_speed(speed, speed_exp_log, delta_seconds);
+
+            *age += delta_seconds;
+            *position += vector![angle.re, angle.im] * displacement;
+            *previous_displacement = displacement;
+        }
+    }
+
+    /// Grows `speed` over `delta_seconds` and returns the distance travelled in that time.
+    pub fn advance_speed(speed: &mut f32, speed_exp_log: f32, delta_seconds: f32) -> f32 {
+        let growth = (speed_exp_log * delta_seconds).exp();
+        let displacement = if speed_exp_log == 0.0 {
+            *speed * delta_seconds
+        } else {
+            *speed * (growth - 1.0) / speed_exp_log
+        };
+
+        *speed *= growth;
+        displacement
+    }
+
+    /// Adds `acceleration` to the velocity described by `angle` and `speed`.
+    pub fn bend(
+        angle: &mut UnitComplex<f32>,
+        speed: &mut f32,
+        acceleration: Vector2<f32>,
+        delta_seconds: f32,
+    ) {
+        let velocity = util::displacement_from_angle(*angle, *speed) + acceleration * delta_seconds;
+
+        *speed = util::length(velocity);
+        *angle = UnitComplex::new(velocity.y.atan2(velocity.x));
+    }
+
+    pub fn is_expired(&self, i: usize) -> bool {
+        self.ages[i] >= self.lifetimes[i]
+    }
+
+    /// Returning `None` indicates that the projectile hit something and should be deleted.
+    fn check_collisions(&mut self, i: usize, app: &mut App) -> Option<()> {
+        let collider = self.get_collider(i);
+        let details = self.details[i];
+
+        if details.interception.point_defense {
+            self.check_interceptions(i, &collider, &app.factions)?;
+        }
+
+        let debris_hit = if app.collision_rules.debris_cover {
+            self.find_debris_hit(i, &collider, &app.debris)
+        } else {
             None
+        };
+
+        let entity_hits = app.entities.iter().filter_map(|(index, entity)| {
+            let rule = if index == details.sender {
+                app.collision_rules.get_self_hit(self.ages[i])
+            } else {
+                app.collision_rules
+                    .get(details.team, entity.team, &app.factions)
+            };
+
+            let multiplier = rule.damage_multiplier()?;
+            self.find_entity_hit(i, &collider, index, entity, multiplier)
+        });
+
+        // Whatever the projectile reached first along its path this timestep takes the hit
+        let Some((_, hit)) = debris_hit
+            .into_iter()
+            .chain(entity_hits)
+            .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+        else {
+            return Some(());
+        };
+
+        let hit = match hit {
+            ProjectileHit::Debris(piece) => {
+                app.debris[piece].damage(details.damage.amount);
+                return None;
+            }
+            ProjectileHit::Shield { entity, multiplier } => {
+                if let Some(shield) = &mut app.entities[entity].shield {
+                    shield.absorb(details.damage.scaled(multiplier));
+                }
+                entity
+            }
+            ProjectileHit::Armor {
+                entity,
+                slot,
+                multiplier,
+            } => {
+                let target = &mut app.entities[entity];
+                target.damage(slot, details.damage.scaled(multiplier), &mut app.debris);
+                if let Some(status) = details.status {
+                    target.status.apply(status);
+                }
+                entity
+            }
+        };
+
+        Emitter::IMPACT.emit(
+            self.positions[i],
+            self.angles[i] * UnitComplex::new(std::f32::consts::PI),
+            app.entities[hit].velocity,
+            details.color,
+            &mut app.particles,
+        );
+
+        app.alert(hit, details.sender);
+
+        if details.blast_radius > 0.0 {
+            app.explode(self.positions[i], details.blast_radius, details.knockback);
         } else {
-            Some(())
+            app.entities[hit].apply_impulse(self.distance_ahead(i, details.knockback));
+        }
+
+        if app.entities[hit].check_deletion().is_none() {
+            app.remove_entity(hit);
+        }
+
+        None
+    }
+
+    /// Destroys the first interceptable projectile this one touches, unless their factions are
+    /// allied. Returning `None` indicates that this projectile was used up.
+    pub fn check_interceptions(
+        &mut self,
+        i: usize,
+        collider: &Rectangle,
+        factions: &Factions,
+    ) -> Option<()> {
+        let center = collider.center();
+        let radius = collider.radius_squared().sqrt() + self.interceptable_reach;
+        let team = self.details[i].team;
+
+        let mut intercepted = None;
+        for other in self.interceptable.query(center, radius) {
+            if other == i
+                || self.is_expired(other)
+                || team
+                    .zip(self.details[other].team)
+                    .is_some_and(|(team, other_team)| {
+                        factions.get(team, other_team) == Relationship::Allied
+                    })
+                || !collider.is_colliding(&self.get_collider(other))
+            {
+                continue;
+            }
+
+            intercepted = Some(other);
+            break;
+        }
+
+        match intercepted {
+            Some(other) => {
+                self.ages[other] = self.lifetimes[other];
+                None
+            }
+            None => Some(()),
         }
     }
 
+    /// Returns the piece of debris this projectile reached first, along with how far along the
+    /// projectile it was reached, for comparing against `find_entity_hit`.
+    fn find_debris_hit(
+        &self,
+        i: usize,
+        collider: &Rectangle,
+        debris: &[Debris],
+    ) -> Option<(f32, ProjectileHit)> {
+        let center = collider.center();
+        let reach = collider.radius_squared().sqrt() + self.debris_reach;
+        let direction = vector![self.angles[i].re, self.angles[i].im];
+
+        self.debris_grid
+            .query(center, reach)
+            .filter_map(|piece| {
+                let rect = debris[piece].get_collider();
+                (!debris[piece].is_expired() && collider.is_colliding(&rect)).then(|| {
+                    (
+                        Self::closest_point(center, direction, &rect),
+                        ProjectileHit::Debris(piece),
+                    )
+                })
+            })
+            .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+    }
+
     pub fn draw(&self) {
-        const FADE_IN_TIME: f32 = 0.1;
-        const FADE_OUT_TIME: f32 = 0.1;
-
-        draw_rectangle_ex(
-            self.position.x,
-            self.position.y,
-            self.size.y.max(self.previous_displacement),
-            self.size.x,
-            DrawRectangleParams {
-                offset: vec2(1.0, 0.5),
-                rotation: self.angle.angle(),
-                color: Color {
-                    a: if self.age < FADE_IN_TIME {
-                        self.age / FADE_IN_TIME
-                    } else if self.age > self.lifetime - FADE_OUT_TIME {
-                        (self.lifetime - self.age) / FADE_OUT_TIME
-                    } else {
-                        1.0
+        for (i, details) in self.details.iter().enumerate() {
+            let age = self.ages[i];
+            let lifetime = self.lifetimes[i];
+
+            draw_rectangle_ex(
+                self.positions[i].x,
+                self.positions[i].y,
+                details.size.y.max(self.previous_displacements[i]),
+                details.size.x,
+                DrawRectangleParams {
+                    offset: vec2(1.0, 0.5),
+                    rotation: self.angles[i].angle(),
+                    color: Color {
+                        a: if age < details.fade_in {
+                            age / details.fade_in
+                        } else if age > lifetime - details.fade_out {
+                            (lifetime - age) / details.fade_out
+                        } else {
+                            1.0
+                        },
+                        ..details.color
                     },
-                    ..self.color
                 },
-            },
-        );
+            );
+        }
     }
 
-    pub fn velocity(&self) -> Vector2<f32> {
-        self.distance_ahead(self.initial_speed)
+    pub fn velocity(&self, i: usize) -> Vector2<f32> {
+        self.distance_ahead(i, self.speeds[i])
     }
 
-    pub fn distance_ahead(&self, distance: f32) -> Vector2<f32> {
-        self.angle * vector![distance, 0.0]
+    pub fn distance_ahead(&self, i: usize, distance: f32) -> Vector2<f32> {
+        self.angles[i] * vector![distance, 0.0]
     }
 
-    pub fn check_collisions_with_entity(
+    /// Returns the shield or armor piece of `entity` this projectile reached first, along with how
+    /// far along the projectile it was reached.
+    fn find_entity_hit(
         &self,
+        i: usize,
         collider: &Rectangle,
-        entity: &mut Entity,
-        direction: UnitComplex<f32>,
-    ) -> Option<()> {
+        index: Index,
+        entity: &Entity,
+        multiplier: f32,
+    ) -> Option<(f32, ProjectileHit)> {
         let center = collider.center();
-        if distance_squared(&center, &entity.position)
-            > (collider.radius_squared().sqrt() + entity.radius).powi(2)
-        {
+        let reach = collider.radius_squared().sqrt();
+        let distance = distance(&center, &entity.position);
+        if distance > reach + entity.get_shield_radius() {
             return None;
         }
 
-        let direction = vector![direction.re, direction.im];
-        if let Some((_, armor)) = (&mut entity.rings)
-            .into_iter()
-            .flat_map(|ring| ring.get_colliders_zip(entity.position))
-            .chain(vec![(
-                entity.center.get_collider(entity.position),
-                &mut entity.center.armor,
-            )])
+        // Only the tip crossing the shell from outside during this timestep counts, so shots fired
+        // from inside pass through
+        let direction = vector![self.angles[i].re, self.angles[i].im];
+        let previous_position = self.positions[i] - direction * self.previous_displacements[i];
+        if let Some(entry) = entity.shield_raycast(
+            previous_position,
+            self.angles[i],
+            self.previous_displacements[i],
+            self.details[i].damage.kind,
+        ) {
+            return Some((
+                entry + (previous_position - center).dot(&direction),
+                ProjectileHit::Shield {
+                    entity: index,
+                    multiplier,
+                },
+            ));
+        }
+
+        if distance > reach + entity.radius {
+            return None;
+        }
+
+        entity
+            .colliders
+            .query(distance - reach..distance + reach)
             .filter(|(rect, _)| collider.is_colliding(rect))
-            .map(|(rect, armor)| {
-                // sort collisions by closest point
+            .map(|(rect, slot)| {
                 (
-                    rect.corners
-                        .into_iter()
-                        .map(|c| (c - center).dot(&direction))
-                        .min_by(|a, b| a.partial_cmp(b).unwrap())
-                        .unwrap(),
-                    armor,
+                    Self::closest_point(center, direction, rect),
+                    ProjectileHit::Armor {
+                        entity: index,
+                        slot: *slot,
+                        multiplier,
+                    },
                 )
             })
             .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
-        {
-            Armor::damage(armor, 1);
-            if let Some(controller) = &mut entity.controller {
-                controller.alert(self.sender);
-            }
-            return Some(());
-        }
+    }
 
-        None
+    /// Distance along `direction` from `center` to the corner of `rect` furthest back, so that
+    /// whatever the projectile swept into first this timestep sorts lowest.
+    fn closest_point(center: Point2<f32>, direction: Vector2<f32>, rect: &Rectangle) -> f32 {
+        rect.corners
+            .into_iter()
+            .map(|c| (c - center).dot(&direction))
+            .min_by(|a, b| a.partial_cmp(b).unwrap())
+            .unwrap()
     }
 
     /// Note that this factors in the previous displacement of the projectile
-    pub fn get_collider(&self) -> Rectangle {
+    pub fn get_collider(&self, i: usize) -> Rectangle {
         Rectangle::from_dimensions(
-            self.position,
-            vector![self.size.y + self.previous_displacement, self.size.x],
+            self.positions[i],
+            vector![
+                self.details[i].size.y + self.previous_displacements[i],
+                self.details[i].size.x
+            ],
             vector![1.0, 0.5],
-            self.angle,
+            self.angles[i],
         )
     }
 }
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Interception {
+    /// Can be shot down by point defense projectiles of factions that aren't allied
+    pub interceptable: bool,
+    /// Destroys interceptable projectiles of factions that aren't allied on contact
+    pub point_defense: bool,
+}
+
+/// Something a projectile ran into, found before any damage is dealt so the closest one wins.
+#[derive(Clone, Copy, Debug)]
+enum ProjectileHit {
+    Debris(usize),
+    Shield {
+        entity: Index,
+        multiplier: f32,
+    },
+    Armor {
+        entity: Index,
+        slot: ArmorSlot,
+        multiplier: f32,
+    },
+}
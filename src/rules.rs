@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+use crate::faction::{Factions, Relationship, Team};
+
+/// What happens when a shot from one team reaches an entity.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CollisionRule {
+    Damage,
+    ReducedDamage { multiplier: f32 },
+    PassThrough,
+}
+
+impl CollisionRule {
+    /// Returns `None` if the shot passes through.
+    pub fn damage_multiplier(self) -> Option<f32> {
+        match self {
+            Self::Damage => Some(1.0),
+            Self::ReducedDamage { multiplier } => Some(multiplier),
+            Self::PassThrough => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CollisionRules {
+    /// Keyed by (shooter team, victim team)
+    pub rules: HashMap<(Team, Team), CollisionRule>,
+    /// Used when a projectile comes back around to the entity that fired it
+    pub self_hit: CollisionRule,
+    /// How long a projectile ignores the entity that fired it, so it can clear its armor first
+    pub self_hit_delay: f32,
+    /// Destroyed armor stops projectiles until it breaks apart or drifts away
+    pub debris_cover: bool,
+}
+
+impl CollisionRules {
+    /// Shots pass through allies and hit everyone else if no rule is set.
+    pub fn get(&self, shooter: Option<Team>, victim: Team, factions: &Factions) -> CollisionRule {
+        let Some(shooter) = shooter else {
+            return CollisionRule::Damage;
+        };
+
+        match self.rules.get(&(shooter, victim)) {
+            Some(&rule) => rule,
+            None if factions.get(shooter, victim) == Relationship::Allied => {
+                CollisionRule::PassThrough
+            }
+            None => CollisionRule::Damage,
+        }
+    }
+
+    pub fn get_self_hit(&self, age: f32) -> CollisionRule {
+        if age < self.self_hit_delay {
+            CollisionRule::PassThrough
+        } else {
+            self.self_hit
+        }
+    }
+}
+
+impl Default for CollisionRules {
+    fn default() -> Self {
+        Self {
+            rules: HashMap::new(),
+            self_hit: CollisionRule::PassThrough,
+            self_hit_delay: 0.25,
+            debris_cover: true,
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use nalgebra::{Point2, UnitComplex, Vector2, vector};
+use thunderdome::Index;
+
+use crate::app::App;
+
+/// Ties an entity's position to a circular orbit around another entity, such as drones circling
+/// the player. The satellite is otherwise a separate entity with its own armor, team and
+/// controller.
+#[derive(Clone, Copy, Debug)]
+pub struct Satellite {
+    pub parent: Index,
+    pub radius: f32,
+    pub angle: f32,
+    pub speed: f32,
+}
+
+impl Satellite {
+    pub fn from_orbit(parent: Index, radius: f32, angle: f32, speed: f32) -> Self {
+        Self {
+            parent,
+            radius,
+            angle,
+            speed,
+        }
+    }
+
+    /// Moves along the orbit, returning the new position and velocity of the satellite. Returns
+    /// `None` if the parent is gone, in which case the satellite should be detached.
+    pub fn update(&mut self, delta_seconds: f32, app: &App) -> Option<(Point2<f32>, Vector2<f32>)> {
+        use std::f32::consts::TAU;
+        let parent = app.entities.get(self.parent)?;
+
+        self.angle += self.speed * delta_seconds;
+        self.angle %= TAU;
+
+        let direction = UnitComplex::new(self.angle);
+        let position = parent.position + direction * vector![self.radius, 0.0];
+        let velocity = parent.velocity + direction * vector![0.0, self.radius * self.speed];
+
+        Some((position, velocity))
+    }
+}
@@ -0,0 +1,183 @@
+use macroquad::prelude::*;
+use nalgebra::Point2;
+
+use crate::damage::{Damage, DamageKind};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StatusKind {
+    /// Scales thrust and top speed
+    Slow {
+        multiplier: f32,
+    },
+    /// Blocks the controller entirely
+    Stun,
+    /// Deals energy damage over time to random armor pieces
+    Burn {
+        damage_per_second: f32,
+    },
+    Jam(JamTarget),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JamTarget {
+    /// Freezes ring rotation
+    Rings,
+    /// Freezes weapon cooldowns, so nothing can fire
+    Weapons,
+}
+
+/// How a new effect combines with effects of the same kind already on the entity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stacking {
+    /// Keeps a single effect, taking the longer duration and the stronger version
+    Refresh,
+    /// Keeps up to `max` separate effects, replacing the one closest to running out after that
+    Stack { max: usize },
+}
+
+impl StatusKind {
+    pub fn stacking(&self) -> Stacking {
+        match self {
+            Self::Burn { .. } => Stacking::Stack { max: 3 },
+            Self::Slow { .. } | Self::Stun | Self::Jam(_) => Stacking::Refresh,
+        }
+    }
+
+    pub fn is_same_kind(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Jam(a), Self::Jam(b)) => a == b,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Self::Slow { .. } => Color::from_hex(0x00c0ff),
+            Self::Stun => Color::from_hex(0xffff00),
+            Self::Burn { .. } => Color::from_hex(0xff6000),
+            Self::Jam(_) => Color::from_hex(0xc000ff),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub duration: f32,
+}
+
+impl StatusEffect {
+    pub fn from_duration(kind: StatusKind, duration: f32) -> Self {
+        Self { kind, duration }
+    }
+}
+
+/// Every timed effect currently on an entity.
+#[derive(Clone, Debug, Default)]
+pub struct StatusEffects {
+    pub effects: Vec<StatusEffect>,
+    pub accumulated_burn: f32,
+}
+
+impl StatusEffects {
+    pub fn apply(&mut self, effect: StatusEffect) {
+        let mut same = self
+            .effects
+            .iter_mut()
+            .filter(|other| other.kind.is_same_kind(&effect.kind));
+
+        match effect.kind.stacking() {
+            Stacking::Refresh => match same.next() {
+                Some(existing) => {
+                    existing.duration = existing.duration.max(effect.duration);
+                    if let (
+                        StatusKind::Slow { multiplier },
+                        StatusKind::Slow {
+                            multiplier: new_multiplier,
+                        },
+                    ) = (&mut existing.kind, effect.kind)
+                    {
+                        *multiplier = multiplier.min(new_multiplier);
+                    }
+                }
+                None => self.effects.push(effect),
+            },
+            Stacking::Stack { max } => {
+                let count = same.count();
+                if count < max {
+                    self.effects.push(effect);
+                } else if let Some(shortest) = self
+                    .effects
+                    .iter_mut()
+                    .filter(|other| other.kind.is_same_kind(&effect.kind))
+                    .min_by(|a, b| a.duration.partial_cmp(&b.duration).unwrap())
+                {
+                    *shortest = effect;
+                }
+            }
+        }
+    }
+
+    /// Counts down every effect, returning the burn damage dealt during this update.
+    pub fn update(&mut self, delta_seconds: f32) -> Option<Damage> {
+        for effect in &mut self.effects {
+            if let StatusKind::Burn { damage_per_second } = effect.kind {
+                self.accumulated_burn += damage_per_second * delta_seconds.min(effect.duration);
+            }
+            effect.duration -= delta_seconds;
+        }
+        self.effects.retain(|effect| effect.duration > 0.0);
+
+        let amount = self.accumulated_burn.floor();
+        self.accumulated_burn -= amount;
+
+        (amount > 0.0).then(|| Damage::from_amount(amount as u16, DamageKind::Energy))
+    }
+
+    /// The strongest slow wins, slows don't multiply together.
+    pub fn speed_multiplier(&self) -> f32 {
+        self.effects
+            .iter()
+            .filter_map(|effect| match effect.kind {
+                StatusKind::Slow { multiplier } => Some(multiplier),
+                _ => None,
+            })
+            .fold(1.0, f32::min)
+    }
+
+    pub fn is_stunned(&self) -> bool {
+        self.effects
+            .iter()
+            .any(|effect| effect.kind == StatusKind::Stun)
+    }
+
+    pub fn is_jammed(&self, target: JamTarget) -> bool {
+        self.effects
+            .iter()
+            .any(|effect| effect.kind == StatusKind::Jam(target))
+    }
+
+    /// Draws a pip for every effect in a row above the entity, blinking as they run out.
+    pub fn draw_around(&self, position: Point2<f32>, radius: f32) {
+        const SPACING: f32 = 1.5;
+        let start = position.x - (self.effects.len() as f32 - 1.0) * SPACING / 2.0;
+
+        for (i, effect) in self.effects.iter().enumerate() {
+            if effect.duration < 0.5 && (effect.duration * 8.0).fract() < 0.5 {
+                continue;
+            }
+
+            draw_rectangle_ex(
+                start + i as f32 * SPACING,
+                position.y - radius - 2.0,
+                1.0,
+                1.0,
+                DrawRectangleParams {
+                    offset: vec2(0.5, 0.5),
+                    rotation: std::f32::consts::TAU / 8.0,
+                    color: effect.kind.color(),
+                },
+            );
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::ops::Range;
 
-use nalgebra::{vector, UnitComplex, Vector2};
+use nalgebra::{Point2, UnitComplex, Vector2, vector};
 
 pub fn length(vector: Vector2<f32>) -> f32 {
     length_squared(vector).sqrt()
@@ -17,3 +17,28 @@ pub fn lerp(range: &Range<f32>, interpolation: f32) -> f32 {
 pub fn displacement_from_angle(angle: UnitComplex<f32>, distance: f32) -> Vector2<f32> {
     vector![angle.re, angle.im] * distance
 }
+
+/// Returns the signed difference `a - b`, wrapped to the range -PI..=PI.
+pub fn angle_difference(a: f32, b: f32) -> f32 {
+    use std::f32::consts::{PI, TAU};
+    let difference = (a - b).rem_euclid(TAU);
+    if difference > PI {
+        difference - TAU
+    } else {
+        difference
+    }
+}
+
+/// Moves something drifting freely by one timestep, pulled by `gravity` and losing `drag` of its
+/// velocity every second.
+pub fn drift(
+    position: &mut Point2<f32>,
+    velocity: &mut Vector2<f32>,
+    gravity: Vector2<f32>,
+    drag: f32,
+    delta_seconds: f32,
+) {
+    *velocity += gravity * delta_seconds;
+    *velocity *= (1.0 - drag).powf(delta_seconds);
+    *position += *velocity * delta_seconds;
+}